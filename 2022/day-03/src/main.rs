@@ -1,6 +1,4 @@
-#![feature(slice_group_by)]
-
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::env;
 use std::fs;
 use std::str;
@@ -14,6 +12,10 @@ fn main() {
     let sum_grouped = get_sum_grouped(&filepath);
     println!("Sum: {}", sum);
     println!("Grouped sum: {}", sum_grouped);
+
+    if let Some("plan") = args.next().as_deref() {
+        print_repair_plans(&filepath);
+    }
 }
 
 fn get_sum(path: &str) -> u32 {
@@ -50,7 +52,7 @@ fn get_sum_grouped(path: &str) -> u32 {
     get_input_lines(&bytes)
         .into_iter()
         .map(|x| {
-            let mut vec = x.iter().cloned().collect::<Vec<u8>>();
+            let mut vec = x.to_vec();
             vec.sort();
             vec.dedup();
             vec
@@ -60,9 +62,8 @@ fn get_sum_grouped(path: &str) -> u32 {
         .map(|x| {
             let mut y = x.iter().flatten().cloned().collect::<Vec<u8>>();
             y.sort();
-            y.group_by(|a, b| a == b)
-                .filter(|x| x.len() == 3)
-                .next()
+            y.chunk_by(|a, b| a == b)
+                .find(|x| x.len() == 3)
                 .expect("")
                 .iter()
                 .cloned()
@@ -73,7 +74,141 @@ fn get_sum_grouped(path: &str) -> u32 {
         .sum()
 }
 
-fn get_input_lines(bytes: &Vec<u8>) -> Vec<&[u8]> {
+fn print_repair_plans(path: &str) {
+    let bytes = fs::read(path).expect("Unable to read file");
+    let mut total_cost = 0;
+
+    for (index, rucksack) in get_input_lines(&bytes).into_iter().enumerate() {
+        match plan_repair(rucksack) {
+            None => println!("{}: impossible", index + 1),
+            Some(plan) => {
+                let swaps = plan
+                    .swaps
+                    .iter()
+                    .map(|swap| {
+                        format!(
+                            "{}<->{}",
+                            rucksack[swap.first_index] as char, rucksack[swap.second_index] as char
+                        )
+                    })
+                    .collect::<Vec<String>>();
+
+                println!(
+                    "{}: {} swaps [{}], cost {}, repaired {}",
+                    index + 1,
+                    plan.swaps.len(),
+                    swaps.join(", "),
+                    plan.cost,
+                    String::from_utf8_lossy(&plan.apply(rucksack))
+                );
+                total_cost += plan.cost;
+            }
+        }
+    }
+
+    println!("Total repair cost: {}", total_cost);
+}
+
+/// Exchanges the item at `first_index` in the first compartment with the item at
+/// `second_index` in the second one. Both indices are positions in the whole rucksack.
+#[derive(Debug, PartialEq)]
+struct Swap {
+    first_index: usize,
+    second_index: usize,
+}
+
+#[derive(Debug, PartialEq)]
+struct RepairPlan {
+    swaps: Vec<Swap>,
+    cost: u32,
+}
+
+impl RepairPlan {
+    fn apply(&self, rucksack: &[u8]) -> Vec<u8> {
+        let mut repaired = rucksack.to_vec();
+        for swap in &self.swaps {
+            repaired.swap(swap.first_index, swap.second_index);
+        }
+
+        repaired
+    }
+}
+
+/// Finds the fewest swaps between the compartments after which no item type is in both,
+/// preferring the plan with the lowest total priority of moved items on ties.
+/// Returns `None` if the item types cannot be split into two equally sized compartments.
+fn plan_repair(rucksack: &[u8]) -> Option<RepairPlan> {
+    if !rucksack.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let half = rucksack.len() / 2;
+    let mut counts: BTreeMap<u8, (usize, usize)> = BTreeMap::new();
+    for (index, item) in rucksack.iter().enumerate() {
+        let count = counts.entry(*item).or_default();
+        if index < half {
+            count.0 += 1;
+        } else {
+            count.1 += 1;
+        }
+    }
+
+    let types = counts.into_iter().collect::<Vec<(u8, (usize, usize))>>();
+
+    // best[i][size] is the cheapest (swaps, cost) for deciding the first i item types
+    // so that the types kept in the first compartment hold `size` items in total,
+    // and kept[i][size] records whether the i-th type was kept in the first compartment.
+    let mut best: Vec<Vec<Option<(usize, u32)>>> = vec![vec![None; half + 1]; types.len() + 1];
+    let mut kept: Vec<Vec<bool>> = vec![vec![false; half + 1]; types.len() + 1];
+    best[0][0] = Some((0, 0));
+
+    for (i, (item, (first, second))) in types.iter().enumerate() {
+        let priority = map_byte_to_score(*item);
+        for size in 0..=half {
+            let Some((swaps, cost)) = best[i][size] else {
+                continue;
+            };
+
+            let to_second = Some((swaps + first, cost + *first as u32 * priority));
+            if best[i + 1][size].is_none() || to_second < best[i + 1][size] {
+                best[i + 1][size] = to_second;
+                kept[i + 1][size] = false;
+            }
+
+            let size = size + first + second;
+            let to_first = Some((swaps, cost + *second as u32 * priority));
+            if size <= half && (best[i + 1][size].is_none() || to_first < best[i + 1][size]) {
+                best[i + 1][size] = to_first;
+                kept[i + 1][size] = true;
+            }
+        }
+    }
+
+    let (_, cost) = best[types.len()][half]?;
+
+    let mut kept_in_first: HashSet<u8> = HashSet::new();
+    let mut size = half;
+    for (i, (item, (first, second))) in types.iter().enumerate().rev() {
+        if kept[i + 1][size] {
+            kept_in_first.insert(*item);
+            size -= first + second;
+        }
+    }
+
+    let from_first = (0..half).filter(|i| !kept_in_first.contains(&rucksack[*i]));
+    let from_second = (half..rucksack.len()).filter(|i| kept_in_first.contains(&rucksack[*i]));
+    let swaps = from_first
+        .zip(from_second)
+        .map(|(first_index, second_index)| Swap {
+            first_index,
+            second_index,
+        })
+        .collect();
+
+    Some(RepairPlan { swaps, cost })
+}
+
+fn get_input_lines(bytes: &[u8]) -> Vec<&[u8]> {
     bytes
         .split(|x| *x == 10u8)
         .filter(|x| match x {
@@ -125,4 +260,38 @@ mod tests {
         let result = get_sum_grouped(INPUT_PATH);
         assert_eq!(result, 2683);
     }
+
+    #[test]
+    fn test_repair_sample() {
+        let bytes = fs::read(TEST_PATH).expect("Unable to read file");
+        for rucksack in get_input_lines(&bytes) {
+            let plan = plan_repair(rucksack).expect("repairable rucksack");
+
+            let repaired = plan.apply(rucksack);
+            let (first, second) = repaired.split_at(repaired.len() / 2);
+            assert!(first.iter().all(|item| !second.contains(item)));
+        }
+    }
+
+    #[test]
+    fn test_repair_edge_cases() {
+        assert_eq!(
+            plan_repair(b"abcd"),
+            Some(RepairPlan {
+                swaps: vec![],
+                cost: 0
+            })
+        );
+        assert_eq!(
+            plan_repair(b"aBba"),
+            Some(RepairPlan {
+                swaps: vec![Swap {
+                    first_index: 0,
+                    second_index: 2
+                }],
+                cost: 3
+            })
+        );
+        assert_eq!(plan_repair(b"aaab"), None);
+    }
 }
@@ -0,0 +1,162 @@
+use crate::index::IntervalIndex;
+use crate::interval::{Interval, IntervalError};
+use std::cmp::Reverse;
+use std::fmt;
+use std::str::FromStr;
+
+/// The section ranges of a group of elves, parsed from a line like `2-4,6-8` or `2-4,6-8,3-5`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    ranges: Vec<Interval>,
+}
+
+impl Assignment {
+    /// Whether some range contains all the others.
+    pub fn contains(&self) -> bool {
+        let widest = self
+            .ranges
+            .iter()
+            .min_by_key(|x| (x.start(), Reverse(x.end())))
+            .expect("at least two ranges");
+
+        self.ranges.iter().all(|x| widest.contains(x))
+    }
+
+    /// Whether every two ranges overlap, which for intervals means they share a section.
+    pub fn all_overlap(&self) -> bool {
+        let latest_start = self.ranges.iter().map(Interval::start).max();
+        let earliest_end = self.ranges.iter().map(Interval::end).min();
+
+        latest_start <= earliest_end
+    }
+
+    /// Whether at least two of the ranges overlap. Once sorted by start, any range that
+    /// overlaps a later one also overlaps the range right after it, so neighbours suffice.
+    pub fn any_overlap(&self) -> bool {
+        let mut sorted = self.ranges.clone();
+        sorted.sort();
+
+        sorted.windows(2).any(|x| x[0].overlaps(&x[1]))
+    }
+
+    pub fn ranges(&self) -> &[Interval] {
+        &self.ranges
+    }
+}
+
+impl FromStr for Assignment {
+    type Err = IntervalError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let ranges = input
+            .split(',')
+            .map(str::parse::<Interval>)
+            .collect::<Result<Vec<Interval>, IntervalError>>()?;
+
+        if ranges.len() < 2 {
+            return Err(IntervalError::Format(input.to_string()));
+        }
+
+        Ok(Assignment { ranges })
+    }
+}
+
+impl fmt::Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges = self
+            .ranges
+            .iter()
+            .map(Interval::to_string)
+            .collect::<Vec<String>>();
+
+        write!(f, "{}", ranges.join(","))
+    }
+}
+
+/// Answers questions spanning all pairs, such as which elves clean a given section.
+pub struct AssignmentIndex {
+    assignments: Vec<Assignment>,
+    index: IntervalIndex<(usize, usize)>,
+}
+
+impl AssignmentIndex {
+    pub fn new(assignments: Vec<Assignment>) -> Self {
+        let index = IntervalIndex::new(assignments.iter().enumerate().flat_map(|(group, a)| {
+            a.ranges()
+                .iter()
+                .copied()
+                .enumerate()
+                .map(move |(elf, interval)| (interval, (group, elf)))
+        }));
+
+        AssignmentIndex { assignments, index }
+    }
+
+    pub fn assignments(&self) -> &[Assignment] {
+        &self.assignments
+    }
+
+    /// The `(group, elf)` positions of every elf whose range contains `section`, in input order.
+    pub fn covering(&self, section: u32) -> Vec<(usize, usize)> {
+        let mut found = self
+            .index
+            .covering(section)
+            .into_iter()
+            .map(|entry| entry.1)
+            .collect::<Vec<(usize, usize)>>();
+        found.sort();
+        found
+    }
+
+    /// The other groups with at least one range overlapping a range of `group`, in input order.
+    pub fn overlapping_groups(&self, group: usize) -> Vec<usize> {
+        let mut found = self.assignments[group]
+            .ranges()
+            .iter()
+            .flat_map(|interval| self.index.overlapping(interval))
+            .map(|entry| entry.1 .0)
+            .filter(|other| *other != group)
+            .collect::<Vec<usize>>();
+        found.sort();
+        found.dedup();
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignment(input: &str) -> Assignment {
+        input.parse().expect("valid assignment")
+    }
+
+    #[test]
+    fn test_group_predicates() {
+        let nested = assignment("2-8,3-7,4-4");
+        assert!(nested.contains());
+        assert!(nested.all_overlap());
+        assert!(nested.any_overlap());
+
+        let chained = assignment("2-4,4-6,6-8");
+        assert!(!chained.contains());
+        assert!(!chained.all_overlap());
+        assert!(chained.any_overlap());
+
+        let spread = assignment("1-1,9-9,3-3,5-7");
+        assert!(!spread.contains());
+        assert!(!spread.all_overlap());
+        assert!(!spread.any_overlap());
+
+        assert!(assignment("1-9,2-2,8-8").any_overlap());
+        assert!(assignment("0-0,0-0").any_overlap());
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        assert_eq!(assignment("2-4,6-8,3-5").ranges().len(), 3);
+        assert_eq!(assignment("2-4,6-8,3-5").to_string(), "2-4,6-8,3-5");
+        assert!("2-4,6-8,5-3".parse::<Assignment>().is_err());
+        assert!("2-4".parse::<Assignment>().is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::interval::Interval;
+
+/// How many assignments cover each section, found with a sweep over the interval bounds
+/// so it only needs O(n log n) time and O(n) memory for n assignments.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Coverage {
+    segments: Vec<(Interval, usize)>,
+}
+
+impl Coverage {
+    pub fn from_intervals<I>(intervals: I) -> Coverage
+    where
+        I: IntoIterator<Item = Interval>,
+    {
+        // The end event is one past the last section, which does not fit in u32 for u32::MAX.
+        let mut events: Vec<(u64, isize)> = vec![];
+        for interval in intervals {
+            events.push((interval.start() as u64, 1));
+            events.push((interval.end() as u64 + 1, -1));
+        }
+
+        events.sort_unstable();
+
+        let mut segments = vec![];
+        let mut depth: isize = 0;
+        let mut iter = events.into_iter().peekable();
+        while let Some((position, change)) = iter.next() {
+            depth += change;
+            while let Some((_, change)) = iter.next_if(|event| event.0 == position) {
+                depth += change;
+            }
+
+            if let Some((next, _)) = iter.peek() {
+                let segment =
+                    Interval::new(position as u32, (*next - 1) as u32).expect("events are sorted");
+                segments.push((segment, depth as usize));
+            }
+        }
+
+        Coverage { segments }
+    }
+
+    /// Disjoint intervals in ascending order, each with the number of assignments covering it.
+    /// They span from the lowest to the highest assigned section, including uncovered gaps.
+    pub fn segments(&self) -> &[(Interval, usize)] {
+        &self.segments
+    }
+
+    /// Sections between the lowest and highest assigned one that nobody cleans.
+    pub fn uncovered(&self) -> Vec<Interval> {
+        self.with_depth(|depth| depth == 0)
+    }
+
+    pub fn covered_once(&self) -> Vec<Interval> {
+        self.with_depth(|depth| depth == 1)
+    }
+
+    pub fn covered_multiple(&self) -> Vec<Interval> {
+        self.with_depth(|depth| depth > 1)
+    }
+
+    /// The highest number of assignments covering a single section, and where it occurs.
+    pub fn max_depth(&self) -> (usize, Vec<Interval>) {
+        let max = self.segments.iter().map(|s| s.1).max().unwrap_or(0);
+        (max, self.with_depth(|depth| depth == max))
+    }
+
+    /// Number of sections cleaned by more than one elf.
+    pub fn redundant_sections(&self) -> u64 {
+        self.covered_multiple().iter().map(Interval::size).sum()
+    }
+
+    /// Number of extra cleanings, so a section cleaned by three elves counts twice.
+    pub fn redundant_cleanings(&self) -> u64 {
+        self.segments
+            .iter()
+            .filter(|s| s.1 > 1)
+            .map(|s| s.0.size() * (s.1 as u64 - 1))
+            .sum()
+    }
+
+    fn with_depth<F>(&self, filter: F) -> Vec<Interval>
+    where
+        F: Fn(usize) -> bool,
+    {
+        Interval::merge_all(self.segments.iter().filter(|s| filter(s.1)).map(|s| s.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coverage(intervals: &[&str]) -> Coverage {
+        Coverage::from_intervals(
+            intervals
+                .iter()
+                .map(|x| x.parse::<Interval>().expect("valid interval")),
+        )
+    }
+
+    fn interval(input: &str) -> Interval {
+        input.parse().expect("valid interval")
+    }
+
+    #[test]
+    fn test_depths() {
+        let coverage = coverage(&["2-4", "3-5", "8-9", "4-4"]);
+        assert_eq!(coverage.uncovered(), vec![interval("6-7")]);
+        assert_eq!(
+            coverage.covered_once(),
+            vec![interval("2-2"), interval("5-5"), interval("8-9")]
+        );
+        assert_eq!(coverage.covered_multiple(), vec![interval("3-4")]);
+        assert_eq!(coverage.max_depth(), (3, vec![interval("4-4")]));
+        assert_eq!(coverage.redundant_sections(), 2);
+        assert_eq!(coverage.redundant_cleanings(), 3);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let coverage = coverage(&["4294967290-4294967295", "0-0"]);
+        assert_eq!(coverage.uncovered(), vec![interval("1-4294967289")]);
+        assert_eq!(coverage.max_depth().0, 1);
+
+        assert_eq!(Coverage::from_intervals(vec![]).max_depth(), (0, vec![]));
+    }
+}
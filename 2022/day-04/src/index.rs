@@ -0,0 +1,147 @@
+use crate::interval::Interval;
+
+/// A static interval tree answering which stored intervals overlap a query in
+/// O(log n + k) time for k results.
+///
+/// Entries are kept sorted by interval and treated as an implicit balanced tree,
+/// where the middle of every range is the root of that range's subtree.
+#[derive(Debug)]
+pub struct IntervalIndex<T> {
+    entries: Vec<(Interval, T)>,
+    max_end: Vec<u32>,
+}
+
+impl<T> IntervalIndex<T> {
+    pub fn new<I>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = (Interval, T)>,
+    {
+        let mut entries = entries.into_iter().collect::<Vec<(Interval, T)>>();
+        entries.sort_by_key(|entry| entry.0);
+
+        let mut max_end = vec![0; entries.len()];
+        build_max_end(&entries, &mut max_end, 0, entries.len());
+
+        IntervalIndex { entries, max_end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All entries whose interval overlaps `interval`, ordered by interval.
+    pub fn overlapping(&self, interval: &Interval) -> Vec<&(Interval, T)> {
+        let mut result = vec![];
+        self.collect_overlapping(interval, 0, self.entries.len(), &mut result);
+        result
+    }
+
+    /// All entries whose interval contains `section`, ordered by interval.
+    pub fn covering(&self, section: u32) -> Vec<&(Interval, T)> {
+        self.overlapping(&Interval::new(section, section).expect("single section"))
+    }
+
+    fn collect_overlapping<'a>(
+        &'a self,
+        interval: &Interval,
+        low: usize,
+        high: usize,
+        result: &mut Vec<&'a (Interval, T)>,
+    ) {
+        if low >= high {
+            return;
+        }
+
+        let middle = (low + high) / 2;
+        if self.max_end[middle] < interval.start() {
+            return;
+        }
+
+        self.collect_overlapping(interval, low, middle, result);
+
+        let entry = &self.entries[middle];
+        if entry.0.start() > interval.end() {
+            return;
+        }
+
+        if entry.0.overlaps(interval) {
+            result.push(entry);
+        }
+
+        self.collect_overlapping(interval, middle + 1, high, result);
+    }
+}
+
+fn build_max_end<T>(
+    entries: &[(Interval, T)],
+    max_end: &mut [u32],
+    low: usize,
+    high: usize,
+) -> u32 {
+    if low >= high {
+        return 0;
+    }
+
+    let middle = (low + high) / 2;
+    let left = build_max_end(entries, max_end, low, middle);
+    let right = build_max_end(entries, max_end, middle + 1, high);
+    max_end[middle] = entries[middle].0.end().max(left).max(right);
+    max_end[middle]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(input: &str) -> Interval {
+        input.parse().expect("valid interval")
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let ranges = [
+            "2-4", "6-8", "2-3", "4-5", "5-7", "7-9", "2-8", "3-7", "0-100",
+        ];
+        let index = IntervalIndex::new(ranges.iter().map(|x| (interval(x), *x)));
+
+        let mut found = index
+            .overlapping(&interval("4-5"))
+            .into_iter()
+            .map(|entry| entry.1)
+            .collect::<Vec<&str>>();
+        found.sort();
+        assert_eq!(found, vec!["0-100", "2-4", "2-8", "3-7", "4-5", "5-7"]);
+
+        assert_eq!(index.covering(100).len(), 1);
+        assert!(index.covering(101).is_empty());
+    }
+
+    #[test]
+    fn test_matches_linear_scan() {
+        let intervals = (0..200u32)
+            .map(|i| {
+                let start = (i * 7919) % 97;
+                interval(&format!("{}-{}", start, start + (i * 31) % 13))
+            })
+            .collect::<Vec<Interval>>();
+        let index = IntervalIndex::new(intervals.iter().cloned().enumerate().map(|(i, x)| (x, i)));
+
+        for query in &intervals {
+            let mut expected = (0..intervals.len())
+                .filter(|i| intervals[*i].overlaps(query))
+                .collect::<Vec<usize>>();
+            let mut found = index
+                .overlapping(query)
+                .into_iter()
+                .map(|entry| entry.1)
+                .collect::<Vec<usize>>();
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::cmp::{max, min};
+use std::fmt;
+use std::str::FromStr;
+
+/// A range of section IDs with both bounds included, so `2-4` covers sections 2, 3 and 4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    start: u32,
+    end: u32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum IntervalError {
+    Format(String),
+    Reversed { start: u32, end: u32 },
+}
+
+impl fmt::Display for IntervalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntervalError::Format(input) => write!(f, "expected a range like 2-4, got {:?}", input),
+            IntervalError::Reversed { start, end } => {
+                write!(f, "range {}-{} ends before it starts", start, end)
+            }
+        }
+    }
+}
+
+impl Interval {
+    pub fn new(start: u32, end: u32) -> Result<Self, IntervalError> {
+        if start > end {
+            return Err(IntervalError::Reversed { start, end });
+        }
+
+        Ok(Interval { start, end })
+    }
+
+    pub fn start(&self) -> u32 {
+        self.start
+    }
+
+    pub fn end(&self) -> u32 {
+        self.end
+    }
+
+    /// Number of sections in the interval; `u64` because `0-4294967295` has 2^32 of them.
+    pub fn size(&self) -> u64 {
+        (self.end - self.start) as u64 + 1
+    }
+
+    pub fn contains(&self, other: &Interval) -> bool {
+        self.start <= other.start && self.end >= other.end
+    }
+
+    pub fn contains_section(&self, section: u32) -> bool {
+        self.start <= section && section <= self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    /// Whether the intervals do not overlap but leave no section between them.
+    pub fn is_adjacent(&self, other: &Interval) -> bool {
+        self.end.checked_add(1) == Some(other.start) || other.end.checked_add(1) == Some(self.start)
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        if !self.overlaps(other) {
+            return None;
+        }
+
+        Some(Interval {
+            start: max(self.start, other.start),
+            end: min(self.end, other.end),
+        })
+    }
+
+    /// The interval covering both, if they overlap or are adjacent.
+    pub fn union(&self, other: &Interval) -> Option<Interval> {
+        if !self.overlaps(other) && !self.is_adjacent(other) {
+            return None;
+        }
+
+        Some(Interval {
+            start: min(self.start, other.start),
+            end: max(self.end, other.end),
+        })
+    }
+
+    /// The sections of `self` not in `other`, as up to two intervals in ascending order.
+    pub fn difference(&self, other: &Interval) -> Vec<Interval> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+
+        let mut parts = vec![];
+        if self.start < other.start {
+            parts.push(Interval {
+                start: self.start,
+                end: other.start - 1,
+            });
+        }
+
+        if self.end > other.end {
+            parts.push(Interval {
+                start: other.end + 1,
+                end: self.end,
+            });
+        }
+
+        parts
+    }
+
+    /// Merges overlapping and adjacent intervals, returning disjoint intervals in ascending order.
+    pub fn merge_all<I>(intervals: I) -> Vec<Interval>
+    where
+        I: IntoIterator<Item = Interval>,
+    {
+        let mut sorted = intervals.into_iter().collect::<Vec<Interval>>();
+        sorted.sort();
+
+        let mut merged: Vec<Interval> = vec![];
+        for interval in sorted {
+            match merged
+                .last_mut()
+                .and_then(|last| last.union(&interval).map(|u| (last, u)))
+            {
+                Some((last, union)) => *last = union,
+                None => merged.push(interval),
+            }
+        }
+
+        merged
+    }
+}
+
+impl FromStr for Interval {
+    type Err = IntervalError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let format_error = || IntervalError::Format(input.to_string());
+        let (start, end) = input.split_once('-').ok_or_else(format_error)?;
+        let start = start.trim().parse::<u32>().map_err(|_| format_error())?;
+        let end = end.trim().parse::<u32>().map_err(|_| format_error())?;
+
+        Interval::new(start, end)
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(input: &str) -> Interval {
+        input.parse().expect("valid interval")
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(interval("2-4"), Interval::new(2, 4).unwrap());
+        assert_eq!(interval("0-4294967295").size(), 1 << 32);
+        assert_eq!(
+            "5-3".parse::<Interval>(),
+            Err(IntervalError::Reversed { start: 5, end: 3 })
+        );
+        assert_eq!(
+            "5".parse::<Interval>(),
+            Err(IntervalError::Format("5".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_predicates() {
+        assert!(interval("2-8").contains(&interval("3-7")));
+        assert!(!interval("3-7").contains(&interval("2-8")));
+        assert!(interval("5-7").overlaps(&interval("7-9")));
+        assert!(!interval("2-3").overlaps(&interval("4-5")));
+        assert!(interval("2-3").is_adjacent(&interval("4-5")));
+        assert!(!interval("2-3").is_adjacent(&interval("3-5")));
+        assert!(!interval("4294967295-4294967295").is_adjacent(&interval("0-0")));
+    }
+
+    #[test]
+    fn test_operations() {
+        assert_eq!(
+            interval("2-6").intersection(&interval("4-8")),
+            Some(interval("4-6"))
+        );
+        assert_eq!(interval("2-3").intersection(&interval("4-5")), None);
+        assert_eq!(
+            interval("2-3").union(&interval("4-5")),
+            Some(interval("2-5"))
+        );
+        assert_eq!(interval("2-3").union(&interval("5-6")), None);
+        assert_eq!(
+            interval("2-8").difference(&interval("4-5")),
+            vec![interval("2-3"), interval("6-8")]
+        );
+        assert_eq!(interval("4-5").difference(&interval("2-8")), vec![]);
+        assert_eq!(
+            interval("0-4294967295").difference(&interval("0-4294967295")),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_merge_all() {
+        let merged = Interval::merge_all(
+            ["6-8", "2-4", "5-5", "10-12", "11-20"]
+                .into_iter()
+                .map(interval),
+        );
+        assert_eq!(merged, vec![interval("2-8"), interval("10-20")]);
+    }
+}
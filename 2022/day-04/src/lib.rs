@@ -0,0 +1,4 @@
+pub mod assignment;
+pub mod coverage;
+pub mod index;
+pub mod interval;
@@ -1,7 +1,8 @@
-use core::ops::Range;
+use day_04::assignment::{Assignment, AssignmentIndex};
+use day_04::coverage::Coverage;
+use day_04::interval::Interval;
 use std::env;
 use std::fs;
-use std::str::FromStr;
 
 const INPUT_PATH: &str = "input.txt";
 
@@ -13,6 +14,27 @@ fn main() {
     let overlap_count = get_overlapping_pair_count(&filepath);
     println!("Score: {}", count);
     println!("Score: {}", overlap_count);
+
+    match args.next().as_deref() {
+        Some("coverage") => print_coverage(&filepath),
+        Some("groups") => {
+            println!("Containing all others: {}", count);
+            println!("Any pair overlapping: {}", overlap_count);
+            println!(
+                "All pairs overlapping: {}",
+                get_fully_overlapping_group_count(&filepath)
+            );
+        }
+        Some("query") => {
+            let kind = args.next().expect("query kind, either section or group");
+            let value = args
+                .next()
+                .and_then(|x| x.parse::<u32>().ok())
+                .expect("a number to query");
+            print_query(&filepath, &kind, value);
+        }
+        _ => {}
+    }
 }
 
 fn get_contained_pair_count(path: &str) -> usize {
@@ -20,64 +42,91 @@ fn get_contained_pair_count(path: &str) -> usize {
 }
 
 fn get_overlapping_pair_count(path: &str) -> usize {
-    get_count(path, Assignment::overlaps)
+    get_count(path, Assignment::any_overlap)
+}
+
+fn get_fully_overlapping_group_count(path: &str) -> usize {
+    get_count(path, Assignment::all_overlap)
 }
 
 fn get_count<F>(path: &str, assignment_filter: F) -> usize
 where
     F: Fn(&Assignment) -> bool,
 {
-    fs::read_to_string(path)
-        .expect("File should exist")
-        .split("\n")
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .map(|line| line.parse::<Assignment>().expect("valid assignment"))
-        .filter(assignment_filter)
+    get_assignments(path)
+        .iter()
+        .filter(|assignment| assignment_filter(assignment))
         .count()
 }
 
-struct Assignment {
-    first: Range<u32>,
-    second: Range<u32>,
+fn get_coverage(path: &str) -> Coverage {
+    Coverage::from_intervals(
+        get_assignments(path)
+            .into_iter()
+            .flat_map(|assignment| assignment.ranges().to_vec()),
+    )
 }
 
-impl Assignment {
-    fn contains(&self) -> bool {
-        (self.first.start <= self.second.start && self.first.end >= self.second.end)
-            || (self.first.start >= self.second.start && self.first.end <= self.second.end)
-    }
-
-    fn overlaps(&self) -> bool {
-        (self.first.end > self.second.start && self.first.start <= self.second.start)
-            || (self.second.end > self.first.start && self.second.start <= self.first.start)
-    }
+fn print_coverage(path: &str) {
+    let coverage = get_coverage(path);
+    let format = |intervals: Vec<Interval>| {
+        intervals
+            .iter()
+            .map(Interval::to_string)
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    let (max_depth, max_depth_sections) = coverage.max_depth();
+    println!("Uncovered: {}", format(coverage.uncovered()));
+    println!("Covered once: {}", format(coverage.covered_once()));
+    println!(
+        "Covered multiple times: {}",
+        format(coverage.covered_multiple())
+    );
+    println!(
+        "Max overlap: {} at {}",
+        max_depth,
+        format(max_depth_sections)
+    );
+    println!("Redundant sections: {}", coverage.redundant_sections());
+    println!("Redundant cleanings: {}", coverage.redundant_cleanings());
 }
 
-impl FromStr for Assignment {
-    type Err = ();
+/// Prints the elves cleaning a section, or the groups overlapping the group on a 1-based line.
+fn print_query(path: &str, kind: &str, value: u32) {
+    let index = AssignmentIndex::new(get_assignments(path));
+    let assignments = index.assignments();
 
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut iter = input.split(",").map(parse_str_to_range);
-        if let Some(first) = iter.next() {
-            if let Some(second) = iter.next() {
-                return Ok(Assignment { first, second });
+    match kind {
+        "section" => {
+            for (group, elf) in index.covering(value) {
+                let interval = assignments[group].ranges()[elf];
+                println!("Line {}, elf {}: {}", group + 1, elf + 1, interval);
             }
         }
-
-        Err(())
-    }
-}
-
-fn parse_str_to_range(input: &str) -> Range<u32> {
-    let mut iter = input.split("-").map(|c| c.parse::<u32>().expect("a digit"));
-    if let Some(start) = iter.next() {
-        if let Some(end) = iter.next() {
-            return start..end + 1;
+        "group" | "pair" => {
+            let group = (value as usize)
+                .checked_sub(1)
+                .filter(|group| *group < assignments.len())
+                .unwrap_or_else(|| panic!("line {} does not exist", value));
+
+            for other in index.overlapping_groups(group) {
+                println!("Line {}: {}", other + 1, assignments[other]);
+            }
         }
+        _ => panic!("Unexpected query {:?}", kind),
     }
+}
 
-    panic!("Failed to parse {:?}", input);
+fn get_assignments(path: &str) -> Vec<Assignment> {
+    fs::read_to_string(path)
+        .expect("File should exist")
+        .split("\n")
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse::<Assignment>().expect("valid assignment"))
+        .collect()
 }
 
 #[cfg(test)]
@@ -109,4 +158,52 @@ mod tests {
         let result = get_overlapping_pair_count(INPUT_PATH);
         assert_eq!(result, 909);
     }
+
+    #[test]
+    fn test_coverage_sample() {
+        let coverage = get_coverage(TEST_PATH);
+        assert_eq!(coverage.uncovered(), vec![]);
+        assert_eq!(coverage.covered_once(), vec!["9-9".parse().unwrap()]);
+        assert_eq!(coverage.max_depth(), (8, vec!["6-6".parse().unwrap()]));
+        assert_eq!(coverage.redundant_sections(), 7);
+        assert_eq!(coverage.redundant_cleanings(), 34);
+    }
+
+    #[test]
+    fn test_query_sample() {
+        let index = AssignmentIndex::new(get_assignments(TEST_PATH));
+        assert_eq!(index.covering(9), vec![(2, 1)]);
+        assert_eq!(index.covering(2), vec![(0, 0), (1, 0), (3, 0), (5, 0)]);
+        assert_eq!(index.overlapping_groups(1), vec![0, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_group_counts_sample() {
+        let result = get_fully_overlapping_group_count(TEST_PATH);
+        assert_eq!(result, 4);
+    }
+
+    #[test]
+    fn test_assignment_bounds() {
+        let assignment = "4294967290-4294967295,4294967295-4294967295"
+            .parse::<Assignment>()
+            .expect("valid assignment");
+        assert!(assignment.contains());
+        assert!(assignment.any_overlap());
+
+        assert!("5-3,1-2".parse::<Assignment>().is_err());
+        assert!("1-2".parse::<Assignment>().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "line 0 does not exist")]
+    fn test_query_line_zero() {
+        print_query(TEST_PATH, "pair", 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "line 7 does not exist")]
+    fn test_query_past_last_line() {
+        print_query(TEST_PATH, "group", 7);
+    }
 }
@@ -0,0 +1,177 @@
+use crate::stacks::{Command, CommandError, Stacks};
+
+/// A model of crane, deciding in which order crates end up when moving several at once.
+pub trait Crane {
+    /// Moves as many of the requested crates as there are; panics on stacks that do not exist.
+    fn execute(&self, command: &Command, stacks: &mut Stacks);
+
+    /// Carries out `command` only if it is valid, otherwise leaves the stacks untouched.
+    fn execute_checked(&self, command: &Command, stacks: &mut Stacks) -> Result<(), CommandError> {
+        stacks.check(command)?;
+        self.execute(command, stacks);
+        Ok(())
+    }
+
+    /// Carries out as much of `command` as possible, skipping it if a stack does not exist.
+    fn execute_permissive(&self, command: &Command, stacks: &mut Stacks) {
+        if let Err(CommandError::UnknownStack(_)) = stacks.check(command) {
+            return;
+        }
+
+        self.execute(command, stacks);
+    }
+}
+
+/// Moves crates one at a time, so they end up in reverse order.
+pub struct CrateMover9000;
+
+/// Moves all crates at once, so they keep their order.
+pub struct CrateMover9001;
+
+/// Lifts at most `capacity` crates at once, splitting bigger moves into batches
+/// that each keep their order.
+pub struct LimitedCrane {
+    pub capacity: usize,
+}
+
+/// Like [`LimitedCrane`], but every other batch is flipped over on the way.
+pub struct AlternatingCrane {
+    pub capacity: usize,
+}
+
+impl Crane for CrateMover9000 {
+    fn execute(&self, command: &Command, stacks: &mut Stacks) {
+        let mut crates = stacks.take(command.from, command.count);
+        crates.reverse();
+        stacks.put(command.to, crates);
+    }
+}
+
+impl Crane for CrateMover9001 {
+    fn execute(&self, command: &Command, stacks: &mut Stacks) {
+        let crates = stacks.take(command.from, command.count);
+        stacks.put(command.to, crates);
+    }
+}
+
+impl Crane for LimitedCrane {
+    fn execute(&self, command: &Command, stacks: &mut Stacks) {
+        move_in_batches(command, stacks, self.capacity, |_| false);
+    }
+}
+
+impl Crane for AlternatingCrane {
+    fn execute(&self, command: &Command, stacks: &mut Stacks) {
+        move_in_batches(command, stacks, self.capacity, |batch| batch % 2 == 1);
+    }
+}
+
+fn move_in_batches<F>(command: &Command, stacks: &mut Stacks, capacity: usize, reverse: F)
+where
+    F: Fn(usize) -> bool,
+{
+    assert!(capacity > 0, "Crane capacity should be positive");
+
+    let mut remaining = command.count;
+    let mut batch = 0;
+    while remaining > 0 {
+        let count = remaining.min(capacity);
+        let mut crates = stacks.take(command.from, count);
+        if reverse(batch) {
+            crates.reverse();
+        }
+
+        stacks.put(command.to, crates);
+        remaining -= count;
+        batch += 1;
+    }
+}
+
+/// Picks a crane by name: `9000`, `9001`, `limited:N` or `alternating:N`,
+/// where `N` is the number of crates lifted at once.
+pub fn from_name(name: &str) -> Option<Box<dyn Crane>> {
+    let (model, capacity) = match name.split_once(':') {
+        Some((model, capacity)) => (model, Some(capacity.parse::<usize>().ok()?)),
+        None => (name, None),
+    };
+
+    match (model, capacity) {
+        ("9000", None) => Some(Box::new(CrateMover9000)),
+        ("9001", None) => Some(Box::new(CrateMover9001)),
+        ("limited", Some(capacity)) if capacity > 0 => Some(Box::new(LimitedCrane { capacity })),
+        ("alternating", Some(capacity)) if capacity > 0 => {
+            Some(Box::new(AlternatingCrane { capacity }))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execute(crane: &dyn Crane, count: usize) -> Vec<String> {
+        let mut stacks = "[A]    \n[B]    \n[C]    \n[D]    \n[E]    \n 1   2 "
+            .parse::<Stacks>()
+            .expect("valid stacks");
+        let command = Command {
+            from: 0,
+            to: 1,
+            count,
+        };
+
+        crane.execute(&command, &mut stacks);
+        stacks.stacks()[1].clone()
+    }
+
+    #[test]
+    fn test_cranes() {
+        assert_eq!(execute(&CrateMover9000, 5), vec!["A", "B", "C", "D", "E"]);
+        assert_eq!(execute(&CrateMover9001, 5), vec!["E", "D", "C", "B", "A"]);
+        assert_eq!(
+            execute(&LimitedCrane { capacity: 2 }, 5),
+            vec!["B", "A", "D", "C", "E"]
+        );
+        assert_eq!(
+            execute(&AlternatingCrane { capacity: 2 }, 5),
+            vec!["B", "A", "C", "D", "E"]
+        );
+    }
+
+    #[test]
+    fn test_checked_and_permissive() {
+        let mut stacks = "[A]    \n 1   2 ".parse::<Stacks>().expect("valid stacks");
+        let too_many = Command {
+            from: 0,
+            to: 1,
+            count: 2,
+        };
+        let unknown = Command {
+            from: 0,
+            to: 2,
+            count: 1,
+        };
+
+        assert!(CrateMover9001
+            .execute_checked(&too_many, &mut stacks)
+            .is_err());
+        assert!(CrateMover9001
+            .execute_checked(&unknown, &mut stacks)
+            .is_err());
+        assert_eq!(stacks.tops(), vec!["A"]);
+
+        CrateMover9001.execute_permissive(&unknown, &mut stacks);
+        assert_eq!(stacks.stacks()[0], vec!["A"]);
+        CrateMover9001.execute_permissive(&too_many, &mut stacks);
+        assert_eq!(stacks.stacks()[1], vec!["A"]);
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert!(from_name("9000").is_some());
+        assert!(from_name("limited:3").is_some());
+        assert!(from_name("limited:0").is_none());
+        assert!(from_name("limited").is_none());
+        assert!(from_name("9002").is_none());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod crane;
+pub mod planner;
+pub mod replay;
+pub mod stacks;
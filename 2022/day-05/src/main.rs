@@ -1,177 +1,392 @@
-extern crate core;
-
-use std::env;
+use day_05::crane::{self, Crane, CrateMover9000, CrateMover9001};
+use day_05::planner;
+use day_05::replay::{self, Replay, StepError};
+use day_05::stacks::{Command, CommandError, Stacks};
+use std::fmt;
 use std::fs;
-use std::str::FromStr;
+use std::io::{self, BufRead};
+use std::{env, process};
 
 const INPUT_PATH: &str = "input.txt";
 
+/// Pass `--permissive` to skip invalid commands instead of stopping at the first one.
 fn main() {
-    let mut args = env::args().skip(1);
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let permissive = args.iter().any(|arg| arg == "--permissive");
+    let mut args = args.into_iter().filter(|arg| arg != "--permissive");
     let filepath = args.next().unwrap_or(INPUT_PATH.to_string());
 
-    let one_by_one = move_one_by_one(&filepath);
+    match args.next().as_deref() {
+        Some("replay") => {
+            let crane = get_crane(args.next());
+            replay_interactively(&filepath, crane.as_ref(), permissive);
+            return;
+        }
+        Some("snapshots") => {
+            let crane = get_crane(args.next());
+            let steps = args
+                .map(|x| x.parse::<usize>().expect("a step number"))
+                .collect::<Vec<usize>>();
+            print_snapshots(&filepath, crane.as_ref(), &steps, permissive);
+            return;
+        }
+        Some("compare") => {
+            let first = get_crane(args.next());
+            let second = get_crane(args.next());
+            print_divergence(&filepath, first.as_ref(), second.as_ref(), permissive);
+            return;
+        }
+        Some("plan") => {
+            let crane = get_crane(args.next());
+            let target = args.next().expect("a file with the target drawing");
+            print_plan(&filepath, &target, crane.as_ref());
+            return;
+        }
+        Some(name) => {
+            let crane = get_crane(Some(name.to_string()));
+            match rearrange(&filepath, crane.as_ref(), permissive) {
+                Ok(stacks) => {
+                    println!("{}", stacks);
+                    println!("{}: {:?}", name, stacks.tops());
+                }
+                Err(error) => {
+                    eprintln!("{}", error);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        None => {}
+    }
+
+    let one_by_one = move_one_by_one(&filepath, permissive);
     println!("One by one: {:?}", one_by_one);
 
-    let at_once = move_all_at_once(&filepath);
+    let at_once = move_all_at_once(&filepath, permissive);
     println!("At once: {:?}", at_once);
 }
 
-fn move_one_by_one(path: &str) -> Vec<char> {
-    execute_command(path, Command::execute_one_by_one)
+fn move_one_by_one(path: &str, permissive: bool) -> Vec<String> {
+    execute_command(path, &CrateMover9000, permissive)
 }
 
-fn move_all_at_once(path: &str) -> Vec<char> {
-    execute_command(path, Command::execute_all_at_once)
+fn move_all_at_once(path: &str, permissive: bool) -> Vec<String> {
+    execute_command(path, &CrateMover9001, permissive)
 }
 
-fn execute_command<F>(path: &str, move_function: F) -> Vec<char>
-where
-    F: Fn(&Command, &mut Stacks) -> (),
-{
-    let content = fs::read_to_string(path).expect("File should exist");
-    let input = parse_input(&content);
-    let mut stacks = input.0.parse::<Stacks>().expect("");
-    let commands = input
-        .1
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| line.parse::<Command>().expect(""));
+fn execute_command(path: &str, crane: &dyn Crane, permissive: bool) -> Vec<String> {
+    rearrange(path, crane, permissive)
+        .unwrap_or_else(|error| exit_with(error))
+        .tops()
+}
 
-    for command in commands {
-        move_function(&command, &mut stacks);
-    }
+/// A command that could not be parsed or carried out, with the stacks as they were before it.
+#[derive(Debug)]
+struct InvalidCommand {
+    line: usize,
+    error: CommandError,
+    stacks: Stacks,
+}
 
-    stacks
-        .stacks
-        .into_iter()
-        .map(|mut stack| stack.pop())
-        .filter(|x| x.is_some())
-        .map(|x| x.expect(""))
-        .collect()
+impl fmt::Display for InvalidCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}\n{}", self.line, self.error, self.stacks)
+    }
 }
 
-fn parse_input(content: &str) -> (&str, &str) {
-    let mut iter = content.split("\n\n").filter(|line| !line.is_empty());
-    if let Some(stacks) = iter.next() {
-        if let Some(commands) = iter.next() {
-            return (stacks, commands);
+fn rearrange(path: &str, crane: &dyn Crane, permissive: bool) -> Result<Stacks, InvalidCommand> {
+    let (mut stacks, commands) = parse_file(path);
+    for (line, command) in commands {
+        let result = match command {
+            Ok(command) if permissive => {
+                crane.execute_permissive(&command, &mut stacks);
+                Ok(())
+            }
+            Ok(command) => crane.execute_checked(&command, &mut stacks),
+            Err(_) if permissive => Ok(()),
+            Err(error) => Err(error),
+        };
+
+        if let Err(error) = result {
+            return Err(InvalidCommand {
+                line,
+                error,
+                stacks,
+            });
         }
     }
 
-    panic!("Failed to parse {:?}", iter);
+    Ok(stacks)
 }
 
-#[derive(Debug)]
-struct Stacks {
-    stacks: Vec<Vec<char>>,
-}
-
-impl FromStr for Stacks {
-    type Err = ();
-
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let lines = input.lines().collect::<Vec<&str>>();
-        let line_count = lines.len();
-        let stack_count = lines.last().expect("").replace(" ", "").len();
-        let raw_stacks = lines
-            .into_iter()
-            .take(line_count - 1)
-            .map(|line| {
-                line.chars()
-                    .collect::<Vec<char>>()
-                    .chunks(4)
-                    .map(|x| x[1])
-                    .collect::<Vec<char>>()
-            })
-            .collect::<Vec<Vec<char>>>();
-
-        let mut stacks: Vec<Vec<char>> = vec![Vec::new(); stack_count];
-
-        for chars in raw_stacks.into_iter().rev() {
-            for (i, char) in chars.into_iter().enumerate() {
-                if char != ' ' {
-                    stacks[i].push(char);
-                }
+/// The stacks, and the commands of the file with their line numbers. Commands that do not
+/// parse are skipped when `permissive`, and otherwise stop the program with their line.
+fn parse_valid_file(path: &str, permissive: bool) -> (Stacks, Vec<usize>, Vec<Command>) {
+    let (stacks, commands) = parse_file(path);
+    let mut lines = vec![];
+    let mut valid = vec![];
+    for (line, command) in commands {
+        match command {
+            Ok(command) => {
+                lines.push(line);
+                valid.push(command);
             }
+            Err(_) if permissive => {}
+            Err(error) => exit_with(InvalidCommand {
+                line,
+                error,
+                stacks,
+            }),
         }
+    }
 
-        Ok(Stacks { stacks })
+    (stacks, lines, valid)
+}
+
+/// Turns a replay error into one that points at the line of the command.
+fn to_invalid_command(error: StepError, lines: &[usize]) -> InvalidCommand {
+    InvalidCommand {
+        line: lines[error.step - 1],
+        error: error.error,
+        stacks: error.stacks,
     }
 }
 
-#[derive(Debug)]
-struct Command {
-    from: usize,
-    to: usize,
-    count: usize,
+fn exit_with(error: InvalidCommand) -> ! {
+    eprintln!("{}", error);
+    process::exit(1);
 }
 
-impl Command {
-    fn execute_one_by_one(&self, stacks: &mut Stacks) {
-        for _ in 0..self.count + 1 {
-            if let Some(value) = stacks.stacks[self.from].pop() {
-                stacks.stacks[self.to].push(value);
+fn get_crane(name: Option<String>) -> Box<dyn Crane> {
+    name.and_then(|name| crane::from_name(&name))
+        .expect("9000, 9001, limited:N or alternating:N")
+}
+
+/// Reads `n` (or an empty line) to step forward, `b` to step back,
+/// `g N` to go to step N and `q` to quit, printing the stacks after each.
+/// An invalid command is reported and the replay stays at the step before it.
+fn replay_interactively(path: &str, crane: &dyn Crane, permissive: bool) {
+    let (stacks, lines, commands) = parse_valid_file(path, permissive);
+    let mut replay = Replay::new(stacks, commands, crane).permissive(permissive);
+    print_step(&replay);
+
+    for line in io::stdin().lock().lines() {
+        let line = line.expect("Should read standard input");
+        let mut words = line.split_whitespace();
+        let result = match (words.next(), words.next().map(str::parse::<usize>)) {
+            (None, _) | (Some("n"), None) => replay.forward().map(|_| ()),
+            (Some("b"), None) => {
+                replay.back();
+                Ok(())
             }
+            (Some("g"), Some(Ok(step))) => replay.jump(step),
+            (Some("q"), None) => return,
+            _ => {
+                println!("Expected n, b, g N or q");
+                continue;
+            }
+        };
+
+        if let Err(error) = result {
+            println!("{}\n", to_invalid_command(error, &lines));
         }
+
+        print_step(&replay);
+    }
+}
+
+fn print_step(replay: &Replay) {
+    match replay.last_command() {
+        Some(command) => println!("Step {}/{}: {}", replay.step(), replay.len(), command),
+        None => println!("Step 0/{}", replay.len()),
+    }
+
+    println!("{}\n", replay.stacks());
+}
+
+fn print_snapshots(path: &str, crane: &dyn Crane, steps: &[usize], permissive: bool) {
+    let (stacks, lines, commands) = parse_valid_file(path, permissive);
+    let mut replay = Replay::new(stacks, commands, crane).permissive(permissive);
+    let snapshots = replay
+        .snapshots(steps)
+        .unwrap_or_else(|error| exit_with(to_invalid_command(error, &lines)));
+
+    for (step, stacks) in snapshots {
+        println!("Step {}/{}", step, replay.len());
+        println!("{}\n", stacks);
+    }
+}
+
+fn print_divergence(path: &str, first: &dyn Crane, second: &dyn Crane, permissive: bool) {
+    let (stacks, lines, commands) = parse_valid_file(path, permissive);
+    let divergence = replay::first_divergence(&stacks, &commands, first, second, permissive)
+        .unwrap_or_else(|error| exit_with(to_invalid_command(error, &lines)));
+    let Some(step) = divergence else {
+        println!("Cranes never diverge");
+        return;
+    };
+
+    println!(
+        "Cranes diverge at step {} (line {}): {}",
+        step,
+        lines[step - 1],
+        commands[step - 1]
+    );
+    for crane in [first, second] {
+        let mut replay =
+            Replay::new(stacks.clone(), commands.clone(), crane).permissive(permissive);
+        replay
+            .jump(step)
+            .expect("commands before the divergence were checked");
+        println!("{}\n", replay.stacks());
     }
+}
+
+const MAX_PLANNED_STATES: usize = 1_000_000;
 
-    fn execute_all_at_once(&self, stacks: &mut Stacks) {
-        let from = &mut stacks.stacks[self.from];
-        let mut values = from
-            .drain(from.len() - self.count - 1..from.len())
-            .collect();
+/// Prints a puzzle input with the drawing of `path` and commands that make `crane`
+/// rearrange it into the drawing of `target_path`.
+fn print_plan(path: &str, target_path: &str, crane: &dyn Crane) {
+    let start = read_drawing(path);
+    let target = read_drawing(target_path);
 
-        stacks.stacks[self.to].append(&mut values);
+    match planner::plan(&start, &target, crane, MAX_PLANNED_STATES) {
+        Some(commands) => {
+            println!("{}\n", start);
+            for command in commands {
+                println!("{}", command);
+            }
+        }
+        None => {
+            eprintln!("No plan found");
+            process::exit(1);
+        }
     }
 }
 
-impl FromStr for Command {
-    type Err = ();
+/// The drawing at the start of a file, which may or may not be followed by commands.
+fn read_drawing(path: &str) -> Stacks {
+    let content = fs::read_to_string(path).expect("File should exist");
+    let drawing = content
+        .split("\n\n")
+        .next()
+        .expect("split returns at least one part");
+
+    drawing
+        .parse::<Stacks>()
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// A command, or why it failed to parse, with its one-based line number.
+type NumberedCommand = (usize, Result<Command, CommandError>);
+
+fn parse_file(path: &str) -> (Stacks, Vec<NumberedCommand>) {
+    let content = fs::read_to_string(path).expect("File should exist");
+    let input = parse_input(&content);
+    let stacks = input
+        .0
+        .parse::<Stacks>()
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    let first_line = input.0.lines().count() + 2;
+    let commands = input
+        .1
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| (first_line + index, line.parse::<Command>()))
+        .collect();
 
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let iter = input
-            .split(" ")
-            .skip(1)
-            .step_by(2)
-            .map(|x| x.parse::<usize>().expect("") - 1)
-            .collect::<Vec<usize>>();
+    (stacks, commands)
+}
 
-        Ok(Command {
-            from: iter[1],
-            to: iter[2],
-            count: iter[0],
-        })
+fn parse_input(content: &str) -> (&str, &str) {
+    let mut iter = content.split("\n\n").filter(|line| !line.is_empty());
+    if let Some(stacks) = iter.next() {
+        if let Some(commands) = iter.next() {
+            return (stacks, commands);
+        }
     }
+
+    panic!("Failed to parse {:?}", iter);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use day_05::crane::LimitedCrane;
 
     const TEST_PATH: &str = "sample.txt";
 
     #[test]
     fn test_sample() {
-        let result = move_one_by_one(TEST_PATH);
-        assert_eq!(result, vec!['C', 'M', 'Z']);
+        let result = move_one_by_one(TEST_PATH, false);
+        assert_eq!(result, vec!["C", "M", "Z"]);
     }
 
     #[test]
     fn test_input() {
-        let result = move_one_by_one(INPUT_PATH);
-        assert_eq!(result, vec!['T', 'L', 'F', 'G', 'B', 'Z', 'H', 'C', 'N']);
+        let result = move_one_by_one(INPUT_PATH, false);
+        assert_eq!(result, vec!["T", "L", "F", "G", "B", "Z", "H", "C", "N"]);
     }
 
     #[test]
     fn test_result_sample() {
-        let result = move_all_at_once(TEST_PATH);
-        assert_eq!(result, vec!['M', 'C', 'D']);
+        let result = move_all_at_once(TEST_PATH, false);
+        assert_eq!(result, vec!["M", "C", "D"]);
     }
 
     #[test]
     fn test_result_input() {
-        let result = move_all_at_once(INPUT_PATH);
-        assert_eq!(result, vec!['Q', 'R', 'Q', 'F', 'H', 'F', 'W', 'C', 'L']);
+        let result = move_all_at_once(INPUT_PATH, false);
+        assert_eq!(result, vec!["Q", "R", "Q", "F", "H", "F", "W", "C", "L"]);
+    }
+
+    #[test]
+    fn test_render_input() {
+        let content = fs::read_to_string(INPUT_PATH).expect("File should exist");
+        let drawing = parse_input(&content).0;
+        assert_eq!(drawing.parse::<Stacks>().expect("").to_string(), drawing);
+
+        let stacks = rearrange(INPUT_PATH, &CrateMover9001, false).expect("valid commands");
+        assert_eq!(stacks.to_string().parse::<Stacks>(), Ok(stacks));
+    }
+
+    #[test]
+    fn test_invalid_commands() {
+        let path = env::temp_dir().join(format!("day-05-invalid-commands-{}.txt", process::id()));
+        let content = fs::read_to_string(TEST_PATH).expect("File should exist");
+        fs::write(
+            &path,
+            content.trim_end().to_string() + "\nmove 2 from 2 to 3\nmove 1 from 4 to 1\nfoo\n",
+        )
+        .expect("Should write file");
+        let checked = rearrange(path.to_str().expect(""), &CrateMover9000, false);
+        let permissive = rearrange(path.to_str().expect(""), &CrateMover9000, true);
+        fs::remove_file(&path).expect("Should remove file");
+
+        let error = checked.expect_err("invalid command");
+        assert_eq!(error.line, 10);
+        assert_eq!(
+            error.error,
+            CommandError::NotEnoughCrates {
+                stack: 2,
+                available: 1,
+                requested: 2
+            }
+        );
+        assert_eq!(error.stacks.tops(), vec!["C", "M", "Z"]);
+
+        let stacks = permissive.expect("permissive");
+        assert_eq!(stacks.tops(), vec!["C", "M"]);
+    }
+
+    #[test]
+    fn test_limited_sample() {
+        let result = execute_command(TEST_PATH, &LimitedCrane { capacity: 1 }, false);
+        assert_eq!(result, move_one_by_one(TEST_PATH, false));
+
+        let result = execute_command(TEST_PATH, &LimitedCrane { capacity: 3 }, false);
+        assert_eq!(result, move_all_at_once(TEST_PATH, false));
     }
 }
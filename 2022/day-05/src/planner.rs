@@ -0,0 +1,169 @@
+use crate::crane::Crane;
+use crate::stacks::{Command, Stacks};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Searches for the shortest list of commands that makes `crane` turn `start` into `target`,
+/// giving up with `None` after visiting `max_states` distinct layouts or if no plan exists.
+///
+/// This is an A* search over layouts. A stack that is not the bottom part of its target
+/// stack has to give away crates at least once, so counting those never overestimates.
+pub fn plan(
+    start: &Stacks,
+    target: &Stacks,
+    crane: &dyn Crane,
+    max_states: usize,
+) -> Option<Vec<Command>> {
+    if !has_same_crates(start, target) {
+        return None;
+    }
+
+    // Every visited layout, with the layout it was reached from and the command used.
+    let mut states: Vec<(Stacks, Option<(usize, Command)>)> = vec![(start.clone(), None)];
+    let mut distances: HashMap<Stacks, usize> = HashMap::from([(start.clone(), 0)]);
+    let mut queue = BinaryHeap::from([Reverse((get_estimate(start, target), 0, 0))]);
+
+    while let Some(Reverse((_, distance, id))) = queue.pop() {
+        let stacks = states[id].0.clone();
+        if &stacks == target {
+            return Some(get_commands(&states, id));
+        }
+
+        if distances[&stacks] < distance {
+            continue;
+        }
+
+        for command in get_possible_commands(&stacks) {
+            let mut next = stacks.clone();
+            crane.execute(&command, &mut next);
+            if distances
+                .get(&next)
+                .is_some_and(|known| *known <= distance + 1)
+            {
+                continue;
+            }
+
+            if states.len() == max_states {
+                return None;
+            }
+
+            let estimate = distance + 1 + get_estimate(&next, target);
+            distances.insert(next.clone(), distance + 1);
+            states.push((next, Some((id, command))));
+            queue.push(Reverse((estimate, distance + 1, states.len() - 1)));
+        }
+    }
+
+    None
+}
+
+fn has_same_crates(start: &Stacks, target: &Stacks) -> bool {
+    start.stacks().len() == target.stacks().len() && get_sorted(start) == get_sorted(target)
+}
+
+fn get_sorted(stacks: &Stacks) -> Vec<&String> {
+    let mut crates = stacks.stacks().iter().flatten().collect::<Vec<&String>>();
+    crates.sort();
+    crates
+}
+
+fn get_estimate(stacks: &Stacks, target: &Stacks) -> usize {
+    stacks
+        .stacks()
+        .iter()
+        .zip(target.stacks())
+        .filter(|(stack, target)| !target.starts_with(stack))
+        .count()
+}
+
+fn get_possible_commands(stacks: &Stacks) -> Vec<Command> {
+    let count = stacks.stacks().len();
+    let mut commands = vec![];
+    for from in 0..count {
+        for to in (0..count).filter(|to| *to != from) {
+            for crates in 1..=stacks.stacks()[from].len() {
+                commands.push(Command {
+                    from,
+                    to,
+                    count: crates,
+                });
+            }
+        }
+    }
+
+    commands
+}
+
+fn get_commands(states: &[(Stacks, Option<(usize, Command)>)], mut id: usize) -> Vec<Command> {
+    let mut commands = vec![];
+    while let Some((parent, command)) = states[id].1 {
+        commands.push(command);
+        id = parent;
+    }
+
+    commands.reverse();
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+
+    fn stacks(drawing: &str) -> Stacks {
+        drawing.parse().expect("valid stacks")
+    }
+
+    fn execute(start: &Stacks, commands: &[Command], crane: &dyn Crane) -> Stacks {
+        let mut stacks = start.clone();
+        for command in commands {
+            crane
+                .execute_checked(command, &mut stacks)
+                .expect("valid command");
+        }
+
+        stacks
+    }
+
+    #[test]
+    fn test_plan() {
+        let start = stacks("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ");
+        let target = stacks("        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3 ");
+
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            let commands = plan(&start, &target, crane, 100_000).expect("a plan");
+            assert!(commands.len() <= 4);
+            assert_eq!(execute(&start, &commands, crane), target);
+        }
+
+        let reversed = stacks("[A]        \n[B]        \n[C]        \n 1   2   3 ");
+        let upright = stacks("[C]        \n[B]        \n[A]        \n 1   2   3 ");
+        let commands = plan(&reversed, &upright, &CrateMover9001, 100_000).expect("a plan");
+        assert_eq!(commands.len(), 4);
+        assert_eq!(execute(&reversed, &commands, &CrateMover9001), upright);
+
+        assert_eq!(plan(&start, &start, &CrateMover9000, 1), Some(vec![]));
+    }
+
+    #[test]
+    fn test_no_plan() {
+        let start = stacks("[A] [B]\n 1   2 ");
+        assert_eq!(
+            plan(&start, &stacks("[A] [C]\n 1   2 "), &CrateMover9000, 1000),
+            None
+        );
+        assert_eq!(
+            plan(
+                &start,
+                &stacks("[A] [B]    \n 1   2   3 "),
+                &CrateMover9000,
+                1000
+            ),
+            None
+        );
+        assert_eq!(
+            plan(&start, &stacks("[B] [A]\n 1   2 "), &CrateMover9000, 1000),
+            None
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use crate::crane::Crane;
+use crate::stacks::{Command, CommandError, Stacks};
+use std::fmt;
+
+/// A command that cannot be carried out, with the one-based step it would have led to
+/// and the stacks it was checked against.
+#[derive(Debug, PartialEq, Eq)]
+pub struct StepError {
+    pub step: usize,
+    pub error: CommandError,
+    pub stacks: Stacks,
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Step {}: {}\n{}", self.step, self.error, self.stacks)
+    }
+}
+
+/// Applies commands one at a time, keeping every visited state so steps can be undone.
+///
+/// Step `n` is the state after the first `n` commands, so step 0 is the starting drawing.
+/// Commands are checked against the stacks they run on, unless the replay is permissive.
+pub struct Replay<'a> {
+    crane: &'a dyn Crane,
+    permissive: bool,
+    commands: Vec<Command>,
+    states: Vec<Stacks>,
+    step: usize,
+}
+
+impl<'a> Replay<'a> {
+    pub fn new(stacks: Stacks, commands: Vec<Command>, crane: &'a dyn Crane) -> Self {
+        Replay {
+            crane,
+            permissive: false,
+            commands,
+            states: vec![stacks],
+            step: 0,
+        }
+    }
+
+    /// Carries out as much of invalid commands as possible instead of stopping at them,
+    /// like [`Crane::execute_permissive`].
+    pub fn permissive(mut self, permissive: bool) -> Self {
+        self.permissive = permissive;
+        self
+    }
+
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// The number of commands, which is also the last step.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn stacks(&self) -> &Stacks {
+        &self.states[self.step]
+    }
+
+    /// The command that leads to the current step, if any.
+    pub fn last_command(&self) -> Option<&Command> {
+        self.step.checked_sub(1).map(|index| &self.commands[index])
+    }
+
+    /// Applies the next command, returning `false` if all commands have been applied.
+    /// An invalid command leaves the current step unchanged.
+    pub fn forward(&mut self) -> Result<bool, StepError> {
+        if self.step == self.commands.len() {
+            return Ok(false);
+        }
+
+        if self.step + 1 == self.states.len() {
+            let mut stacks = self.states[self.step].clone();
+            let command = &self.commands[self.step];
+            apply(self.crane, self.permissive, command, &mut stacks).map_err(|error| {
+                StepError {
+                    step: self.step + 1,
+                    error,
+                    stacks: self.states[self.step].clone(),
+                }
+            })?;
+            self.states.push(stacks);
+        }
+
+        self.step += 1;
+        Ok(true)
+    }
+
+    /// Undoes the last applied command, returning `false` if already at the start.
+    pub fn back(&mut self) -> bool {
+        if self.step == 0 {
+            return false;
+        }
+
+        self.step -= 1;
+        true
+    }
+
+    /// Moves to the given step, stopping at the last one if `step` is past the end.
+    /// If a command on the way is invalid, stops at the step before it.
+    pub fn jump(&mut self, step: usize) -> Result<(), StepError> {
+        let step = step.min(self.commands.len());
+        while self.states.len() <= step {
+            self.step = self.states.len() - 1;
+            self.forward()?;
+        }
+
+        self.step = step;
+        Ok(())
+    }
+
+    /// The state at each of the given steps, leaving the current step unchanged.
+    pub fn snapshots(&mut self, steps: &[usize]) -> Result<Vec<(usize, Stacks)>, StepError> {
+        let current = self.step;
+        let snapshots = steps
+            .iter()
+            .map(|step| {
+                self.jump(*step)?;
+                Ok((self.step, self.stacks().clone()))
+            })
+            .collect();
+
+        self.step = current;
+        snapshots
+    }
+}
+
+/// The first step at which the two cranes leave the stacks in a different state.
+/// Both cranes run on the same stacks until then, so a command is only checked once.
+pub fn first_divergence(
+    stacks: &Stacks,
+    commands: &[Command],
+    first: &dyn Crane,
+    second: &dyn Crane,
+    permissive: bool,
+) -> Result<Option<usize>, StepError> {
+    let mut first_stacks = stacks.clone();
+    let mut second_stacks = stacks.clone();
+
+    for (index, command) in commands.iter().enumerate() {
+        if !permissive {
+            first_stacks.check(command).map_err(|error| StepError {
+                step: index + 1,
+                error,
+                stacks: first_stacks.clone(),
+            })?;
+        }
+
+        apply(first, permissive, command, &mut first_stacks).expect("checked above");
+        apply(second, permissive, command, &mut second_stacks).expect("checked above");
+        if first_stacks != second_stacks {
+            return Ok(Some(index + 1));
+        }
+    }
+
+    Ok(None)
+}
+
+fn apply(
+    crane: &dyn Crane,
+    permissive: bool,
+    command: &Command,
+    stacks: &mut Stacks,
+) -> Result<(), CommandError> {
+    match permissive {
+        true => {
+            crane.execute_permissive(command, stacks);
+            Ok(())
+        }
+        false => crane.execute_checked(command, stacks),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+
+    fn sample() -> (Stacks, Vec<Command>) {
+        let stacks = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 "
+            .parse::<Stacks>()
+            .expect("valid stacks");
+        let commands = [
+            "move 1 from 2 to 1",
+            "move 3 from 1 to 3",
+            "move 2 from 2 to 1",
+            "move 1 from 1 to 2",
+        ]
+        .iter()
+        .map(|x| x.parse::<Command>().expect("valid command"))
+        .collect();
+
+        (stacks, commands)
+    }
+
+    #[test]
+    fn test_step_and_undo() {
+        let (stacks, commands) = sample();
+        let mut replay = Replay::new(stacks.clone(), commands, &CrateMover9000);
+
+        assert!(!replay.back());
+        assert_eq!(replay.forward(), Ok(true));
+        assert_eq!(replay.stacks().tops(), vec!["D", "C", "P"]);
+        assert!(replay.back());
+        assert_eq!(replay.stacks(), &stacks);
+
+        assert_eq!(replay.jump(10), Ok(()));
+        assert_eq!(replay.step(), 4);
+        assert_eq!(replay.stacks().tops(), vec!["C", "M", "Z"]);
+        assert_eq!(replay.forward(), Ok(false));
+
+        assert_eq!(replay.jump(2), Ok(()));
+        assert_eq!(replay.last_command().map(|x| x.count), Some(3));
+        assert_eq!(replay.forward(), Ok(true));
+        assert_eq!(replay.step(), 3);
+    }
+
+    #[test]
+    fn test_invalid_command() {
+        let (stacks, mut commands) = sample();
+        commands.insert(1, "move 1 from 9 to 1".parse().expect("valid command"));
+
+        let mut replay = Replay::new(stacks.clone(), commands.clone(), &CrateMover9000);
+        let error = replay.jump(3).expect_err("invalid command");
+        assert_eq!(error.step, 2);
+        assert_eq!(error.error, CommandError::UnknownStack(9));
+        assert_eq!(error.stacks.tops(), vec!["D", "C", "P"]);
+        assert_eq!(replay.step(), 1);
+        assert!(replay.forward().is_err());
+
+        let mut replay =
+            Replay::new(stacks.clone(), commands.clone(), &CrateMover9000).permissive(true);
+        assert_eq!(replay.jump(5), Ok(()));
+        assert_eq!(replay.stacks().tops(), vec!["C", "M", "Z"]);
+
+        let error = first_divergence(&stacks, &commands, &CrateMover9000, &CrateMover9001, false)
+            .expect_err("invalid command");
+        assert_eq!(error.step, 2);
+        assert_eq!(
+            first_divergence(&stacks, &commands, &CrateMover9000, &CrateMover9001, true),
+            Ok(Some(3))
+        );
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let (stacks, commands) = sample();
+        let mut replay = Replay::new(stacks.clone(), commands, &CrateMover9001);
+        replay.forward().expect("valid command");
+
+        let snapshots = replay.snapshots(&[0, 4]).expect("valid commands");
+        assert_eq!(snapshots[0], (0, stacks));
+        assert_eq!(snapshots[1].1.tops(), vec!["M", "C", "D"]);
+        assert_eq!(replay.step(), 1);
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let (stacks, commands) = sample();
+        let divergence =
+            first_divergence(&stacks, &commands, &CrateMover9000, &CrateMover9001, false);
+        assert_eq!(divergence, Ok(Some(2)));
+        assert_eq!(
+            first_divergence(
+                &stacks,
+                &commands[..1],
+                &CrateMover9000,
+                &CrateMover9001,
+                false
+            ),
+            Ok(None)
+        );
+    }
+}
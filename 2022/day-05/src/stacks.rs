@@ -0,0 +1,428 @@
+use std::cmp::Reverse;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stacks {
+    stacks: Vec<Vec<String>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StacksError {
+    MissingFooter,
+    InvalidLabel { label: String, expected: usize },
+    UnclosedCrate { line: usize, column: usize },
+    UnexpectedCharacter { line: usize, column: usize },
+    UnalignedCrate { line: usize, column: usize },
+}
+
+impl fmt::Display for StacksError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StacksError::MissingFooter => write!(f, "drawing has no stack number footer"),
+            StacksError::InvalidLabel { label, expected } => {
+                write!(f, "expected stack number {}, got {:?}", expected, label)
+            }
+            StacksError::UnclosedCrate { line, column } => {
+                write!(f, "crate at line {}, column {} is not closed", line, column)
+            }
+            StacksError::UnexpectedCharacter { line, column } => {
+                write!(
+                    f,
+                    "unexpected character at line {}, column {}",
+                    line, column
+                )
+            }
+            StacksError::UnalignedCrate { line, column } => write!(
+                f,
+                "crate at line {}, column {} is not above any stack number",
+                line, column
+            ),
+        }
+    }
+}
+
+impl Stacks {
+    /// The crates of every stack, from bottom to top.
+    pub fn stacks(&self) -> &[Vec<String>] {
+        &self.stacks
+    }
+
+    /// The top crate of every non-empty stack.
+    pub fn tops(&self) -> Vec<String> {
+        self.stacks
+            .iter()
+            .filter_map(|stack| stack.last())
+            .cloned()
+            .collect()
+    }
+
+    /// Removes up to `count` crates from the top of stack `from`, returned from bottom to top.
+    pub fn take(&mut self, from: usize, count: usize) -> Vec<String> {
+        let stack = &mut self.stacks[from];
+        let start = stack.len().saturating_sub(count);
+        stack.split_off(start)
+    }
+
+    /// Whether `command` refers to existing stacks and does not move more crates than there are.
+    /// Stack numbers in the error are one-based, as in the drawing.
+    pub fn check(&self, command: &Command) -> Result<(), CommandError> {
+        for stack in [command.from, command.to] {
+            if stack >= self.stacks.len() {
+                return Err(CommandError::UnknownStack(stack + 1));
+            }
+        }
+
+        let available = self.stacks[command.from].len();
+        if available < command.count {
+            return Err(CommandError::NotEnoughCrates {
+                stack: command.from + 1,
+                available,
+                requested: command.count,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Places `crates` on stack `to`, in order from bottom to top.
+    pub fn put(&mut self, to: usize, mut crates: Vec<String>) {
+        self.stacks[to].append(&mut crates);
+    }
+}
+
+/// Parses a drawing whose last line numbers the stacks from 1. Each bracketed crate
+/// belongs to the stack whose number it is drawn above, so columns may have any width,
+/// numbers any number of digits, and lines may lack trailing whitespace.
+impl FromStr for Stacks {
+    type Err = StacksError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let lines = input
+            .lines()
+            .map(|line| line.chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>();
+        let (footer, rows) = lines.split_last().ok_or(StacksError::MissingFooter)?;
+
+        let labels = get_words(footer);
+        for (index, (_, label)) in labels.iter().enumerate() {
+            if label.parse::<usize>() != Ok(index + 1) {
+                return Err(StacksError::InvalidLabel {
+                    label: label.clone(),
+                    expected: index + 1,
+                });
+            }
+        }
+
+        let mut stacks: Vec<Vec<String>> = vec![Vec::new(); labels.len()];
+
+        for (line, row) in rows.iter().enumerate().rev() {
+            for (span, label) in get_crates(row, line + 1)? {
+                let stack = labels
+                    .iter()
+                    .map(|(label_span, _)| get_overlap(&span, label_span))
+                    .enumerate()
+                    .filter(|(_, overlap)| *overlap > 0)
+                    .max_by_key(|(index, overlap)| (*overlap, Reverse(*index)))
+                    .map(|(index, _)| index)
+                    .ok_or(StacksError::UnalignedCrate {
+                        line: line + 1,
+                        column: span.start + 1,
+                    })?;
+
+                stacks[stack].push(label);
+            }
+        }
+
+        Ok(Stacks { stacks })
+    }
+}
+
+/// Renders the stacks as the puzzle's drawing, which [`Stacks::from_str`] parses back.
+/// Columns are widened to fit the longest crate label or stack number.
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .stacks
+            .iter()
+            .flatten()
+            .map(|label| label.chars().count() + 2)
+            .chain([3, self.stacks.len().to_string().len()])
+            .max()
+            .unwrap_or(3);
+
+        let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let row = self
+                .stacks
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(label) => format!("{:^width$}", format!("[{}]", label)),
+                    None => " ".repeat(width),
+                })
+                .collect::<Vec<String>>();
+
+            writeln!(f, "{}", row.join(" "))?;
+        }
+
+        let footer = (1..=self.stacks.len())
+            .map(|number| format!("{:^width$}", number))
+            .collect::<Vec<String>>();
+
+        write!(f, "{}", footer.join(" "))
+    }
+}
+
+/// The whitespace separated words of a line, with the columns they span.
+fn get_words(line: &[char]) -> Vec<(Range<usize>, String)> {
+    let mut words = vec![];
+    let mut start = None;
+    for (column, char) in line.iter().chain([&' ']).enumerate() {
+        match (start, char.is_whitespace()) {
+            (None, false) => start = Some(column),
+            (Some(word_start), true) => {
+                words.push((
+                    word_start..column,
+                    line[word_start..column].iter().collect(),
+                ));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    words
+}
+
+/// The bracketed crates of a drawing line, with the columns they span including brackets.
+fn get_crates(row: &[char], line: usize) -> Result<Vec<(Range<usize>, String)>, StacksError> {
+    let mut crates = vec![];
+    let mut column = 0;
+    while column < row.len() {
+        match row[column] {
+            '[' => {
+                let length = row[column..].iter().position(|x| *x == ']').ok_or(
+                    StacksError::UnclosedCrate {
+                        line,
+                        column: column + 1,
+                    },
+                )?;
+
+                let label = row[column + 1..column + length].iter().collect();
+                crates.push((column..column + length + 1, label));
+                column += length + 1;
+            }
+            x if x.is_whitespace() => column += 1,
+            _ => {
+                return Err(StacksError::UnexpectedCharacter {
+                    line,
+                    column: column + 1,
+                })
+            }
+        }
+    }
+
+    Ok(crates)
+}
+
+fn get_overlap(first: &Range<usize>, second: &Range<usize>) -> usize {
+    first
+        .end
+        .min(second.end)
+        .saturating_sub(first.start.max(second.start))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    Format(String),
+    UnknownStack(usize),
+    NotEnoughCrates {
+        stack: usize,
+        available: usize,
+        requested: usize,
+    },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Format(input) => {
+                write!(
+                    f,
+                    "expected a command like move 1 from 2 to 3, got {:?}",
+                    input
+                )
+            }
+            CommandError::UnknownStack(stack) => write!(f, "stack {} does not exist", stack),
+            CommandError::NotEnoughCrates {
+                stack,
+                available,
+                requested,
+            } => write!(
+                f,
+                "cannot move {} crates from stack {}, which has {}",
+                requested, stack, available
+            ),
+        }
+    }
+}
+
+/// A `move 3 from 1 to 2` step, with zero-based stack indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Command {
+    pub from: usize,
+    pub to: usize,
+    pub count: usize,
+}
+
+impl FromStr for Command {
+    type Err = CommandError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let words = input.split_whitespace().collect::<Vec<&str>>();
+        let numbers = match words[..] {
+            ["move", count, "from", from, "to", to] => [count, from, to]
+                .iter()
+                .map(|x| x.parse::<usize>())
+                .collect::<Result<Vec<usize>, _>>()
+                .ok(),
+            _ => None,
+        };
+
+        match numbers.as_deref() {
+            Some(&[count, from, to]) if from > 0 && to > 0 => Ok(Command {
+                from: from - 1,
+                to: to - 1,
+                count,
+            }),
+            _ => Err(CommandError::Format(input.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.count,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DRAWING: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+
+    #[test]
+    fn test_render_drawing() {
+        let stacks = DRAWING.parse::<Stacks>().expect("valid stacks");
+        assert_eq!(stacks.to_string(), DRAWING);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut stacks = DRAWING.parse::<Stacks>().expect("valid stacks");
+        let crates = stacks.take(0, 2);
+        stacks.put(2, crates);
+
+        assert_eq!(stacks.to_string().parse::<Stacks>(), Ok(stacks.clone()));
+
+        let crates = stacks.take(1, 3);
+        stacks.put(2, crates);
+        assert!(stacks.stacks()[0].is_empty());
+        assert_eq!(stacks.to_string().parse::<Stacks>(), Ok(stacks));
+    }
+
+    #[test]
+    fn test_many_stacks() {
+        let drawing = "[A]                                     [K]\n\
+                       [B] [C] [D] [E] [F] [G] [H] [I] [J] [L] [M]\n \
+                       1   2   3   4   5   6   7   8   9   10  11";
+        let stacks = drawing.parse::<Stacks>().expect("valid stacks");
+        assert_eq!(stacks.stacks().len(), 11);
+        assert_eq!(stacks.stacks()[0], vec!["B", "A"]);
+        assert_eq!(stacks.stacks()[9], vec!["L"]);
+        assert_eq!(stacks.stacks()[10], vec!["M", "K"]);
+        assert_eq!(stacks.to_string().parse::<Stacks>(), Ok(stacks));
+    }
+
+    #[test]
+    fn test_irregular_drawing() {
+        let drawing = "     [AB]\n[C]  [D]   [E]\n 1    2     3";
+        let stacks = drawing.parse::<Stacks>().expect("valid stacks");
+        assert_eq!(stacks.tops(), vec!["C", "AB", "E"]);
+        assert_eq!(
+            stacks.to_string(),
+            "     [AB]     \n[C]  [D]  [E] \n 1    2    3  "
+        );
+        assert_eq!(stacks.to_string().parse::<Stacks>(), Ok(stacks));
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            "move 12 from 10 to 1".parse::<Command>(),
+            Ok(Command {
+                from: 9,
+                to: 0,
+                count: 12
+            })
+        );
+
+        for input in [
+            "move 1 from 0 to 1",
+            "move 1 from 2",
+            "move x from 1 to 2",
+            "",
+        ] {
+            let error = CommandError::Format(input.to_string());
+            assert_eq!(input.parse::<Command>(), Err(error));
+        }
+    }
+
+    #[test]
+    fn test_check_command() {
+        let stacks = DRAWING.parse::<Stacks>().expect("valid stacks");
+        let command = |input: &str| input.parse::<Command>().expect("valid command");
+
+        assert_eq!(stacks.check(&command("move 3 from 2 to 1")), Ok(()));
+        assert_eq!(
+            stacks.check(&command("move 1 from 2 to 4")),
+            Err(CommandError::UnknownStack(4))
+        );
+        assert_eq!(
+            stacks.check(&command("move 2 from 3 to 1")),
+            Err(CommandError::NotEnoughCrates {
+                stack: 3,
+                available: 1,
+                requested: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_drawing() {
+        assert_eq!("".parse::<Stacks>(), Err(StacksError::MissingFooter));
+        assert_eq!(
+            "[A]\n 2 ".parse::<Stacks>(),
+            Err(StacksError::InvalidLabel {
+                label: "2".to_string(),
+                expected: 1
+            })
+        );
+        assert_eq!(
+            "[A\n 1 ".parse::<Stacks>(),
+            Err(StacksError::UnclosedCrate { line: 1, column: 1 })
+        );
+        assert_eq!(
+            "    [A]\n 1 ".parse::<Stacks>(),
+            Err(StacksError::UnalignedCrate { line: 1, column: 5 })
+        );
+    }
+}
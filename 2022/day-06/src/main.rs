@@ -1,5 +1,6 @@
 use std::env;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
 
 const INPUT_PATH: &str = "input.txt";
 
@@ -7,57 +8,370 @@ fn main() {
     let mut args = env::args().skip(1);
     let filepath = args.next().unwrap_or(INPUT_PATH.to_string());
 
-    let first = get_first_marker_index(&filepath, 4);
-    println!("First: {:?}", first);
+    let mode = args.next();
+    let window = args.next().map_or(4, |x| x.parse().expect("a window size"));
+    match mode.as_deref() {
+        Some("stream") => {
+            print_streamed_markers(&filepath, window).expect("Should read the stream");
+            return;
+        }
+        Some("all") => {
+            print_all_markers(&filepath, window, true);
+            return;
+        }
+        Some("disjoint") => {
+            print_all_markers(&filepath, window, false);
+            return;
+        }
+        _ => {}
+    }
+
+    for (line, markers) in get_first_marker_indices(&filepath, &[4, 14]) {
+        println!(
+            "Line {}: first {}, second {}",
+            line,
+            format_marker(markers[0]),
+            format_marker(markers[1])
+        );
+    }
+}
+
+fn format_marker(marker: Option<usize>) -> String {
+    marker.map_or("none".to_string(), |index| index.to_string())
+}
+
+fn print_all_markers(path: &str, window: usize, overlapping: bool) {
+    for (line, markers) in get_all_markers(path, window, overlapping) {
+        let markers = markers
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<String>>();
+        println!("Line {}: {}", line, markers.join(" "));
+    }
+}
+
+/// Prints the offset after each marker as soon as it is read; `-` reads standard input.
+fn print_streamed_markers(path: &str, window: usize) -> io::Result<()> {
+    let reader: Box<dyn Read> = match path {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(File::open(path)?),
+    };
+
+    let mut stdout = io::stdout().lock();
+    for offset in MarkerStream::new(reader, window) {
+        writeln!(stdout, "{}", offset?)?;
+        stdout.flush()?;
+    }
 
-    let second = get_first_marker_index(&filepath, 14);
-    println!("Second: {:?}", second);
+    Ok(())
 }
 
-fn get_first_marker_index(path: &str, window: usize) -> usize {
+/// The first marker for each window size of every line, each line being a separate
+/// datastream, with its line number.
+fn get_first_marker_indices(path: &str, windows: &[usize]) -> Vec<(usize, Vec<Option<usize>>)> {
+    get_lines(path)
+        .into_iter()
+        .map(|(number, line)| {
+            let markers = windows
+                .iter()
+                .map(|&window| find_first_marker(&line, window))
+                .collect();
+            (number, markers)
+        })
+        .collect()
+}
+
+/// Every marker of every line, each line being a separate datastream, with its line number.
+fn get_all_markers(path: &str, window: usize, overlapping: bool) -> Vec<(usize, Vec<usize>)> {
+    get_lines(path)
+        .into_iter()
+        .map(|(number, line)| (number, find_markers(&line, window, overlapping)))
+        .collect()
+}
+
+/// The non-empty lines of the file with their one-based line numbers in the file.
+fn get_lines(path: &str) -> Vec<(usize, String)> {
     let content = fs::read_to_string(path).expect("File should exist");
-    let line = content.lines().next().expect("Should have single line");
-    let chars = line.chars().collect::<Vec<char>>();
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| (index + 1, line.to_string()))
+        .collect()
+}
 
-    for (i, c) in chars.windows(window).enumerate() {
-        let mut x = c.iter().cloned().collect::<Vec<char>>();
-        x.sort();
-        x.dedup();
-        if x.len() == window {
-            return i + window;
+/// The number of characters read when the first marker is complete, if there is one.
+fn find_first_marker(line: &str, window: usize) -> Option<usize> {
+    find_markers(line, window, false).first().copied()
+}
+
+/// The end of every marker in the line. Overlapping markers end wherever the preceding
+/// `window` characters are all different; otherwise each marker starts after the previous one.
+fn find_markers(line: &str, window: usize, overlapping: bool) -> Vec<usize> {
+    let mut detector = MarkerDetector::new(window);
+    let mut markers = vec![];
+    for byte in line.bytes() {
+        if detector.push(byte) {
+            markers.push(detector.position);
+            if !overlapping {
+                detector.run_start = detector.position;
+            }
+        }
+    }
+
+    markers
+}
+
+/// Tracks where each byte was last seen, and where the current run of distinct bytes
+/// starts, so every byte is handled in constant time and memory whatever the window.
+struct MarkerDetector {
+    window: usize,
+    last_seen: [usize; 256],
+    run_start: usize,
+    position: usize,
+}
+
+impl MarkerDetector {
+    fn new(window: usize) -> Self {
+        MarkerDetector {
+            window,
+            last_seen: [0; 256],
+            run_start: 0,
+            position: 0,
+        }
+    }
+
+    /// Whether the last `window` bytes, up to and including `byte`, are all different.
+    fn push(&mut self, byte: u8) -> bool {
+        // Positions are stored one-based, so that zero means not seen yet.
+        let last = self.last_seen[byte as usize];
+        self.run_start = self.run_start.max(last);
+        self.position += 1;
+        self.last_seen[byte as usize] = self.position;
+
+        self.position - self.run_start >= self.window
+    }
+}
+
+/// Reads a datastream in fixed-size chunks, yielding the absolute byte offset right after
+/// every marker as soon as the chunk containing it is read.
+struct MarkerStream<R> {
+    reader: R,
+    detector: MarkerDetector,
+    buffer: [u8; 4096],
+    filled: usize,
+    index: usize,
+}
+
+impl<R: Read> MarkerStream<R> {
+    fn new(reader: R, window: usize) -> Self {
+        MarkerStream {
+            reader,
+            detector: MarkerDetector::new(window),
+            buffer: [0; 4096],
+            filled: 0,
+            index: 0,
         }
     }
+}
+
+impl<R: Read> Iterator for MarkerStream<R> {
+    type Item = io::Result<usize>;
 
-    0
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while self.index < self.filled {
+                let byte = self.buffer[self.index];
+                self.index += 1;
+                if self.detector.push(byte) {
+                    return Some(Ok(self.detector.position));
+                }
+            }
+
+            match self.reader.read(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(read) => {
+                    self.filled = read;
+                    self.index = 0;
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::process;
+    use std::time::Instant;
 
     const TEST_PATH: &str = "sample.txt";
 
     #[test]
     fn test_sample() {
-        let result = get_first_marker_index(TEST_PATH, 4);
-        assert_eq!(result, 7);
+        let result = get_first_marker_indices(TEST_PATH, &[4]);
+        assert_eq!(result, vec![(1, vec![Some(7)])]);
     }
 
     #[test]
     fn test_input() {
-        let result = get_first_marker_index(INPUT_PATH, 4);
-        assert_eq!(result, 1850);
+        let result = get_first_marker_indices(INPUT_PATH, &[4]);
+        assert_eq!(result, vec![(1, vec![Some(1850)])]);
     }
 
     #[test]
     fn test_second_sample() {
-        let result = get_first_marker_index(TEST_PATH, 14);
-        assert_eq!(result, 19);
+        let result = get_first_marker_indices(TEST_PATH, &[14]);
+        assert_eq!(result, vec![(1, vec![Some(19)])]);
     }
 
     #[test]
     fn test_second_input() {
-        let result = get_first_marker_index(INPUT_PATH, 14);
-        assert_eq!(result, 2823);
+        let result = get_first_marker_indices(INPUT_PATH, &[14]);
+        assert_eq!(result, vec![(1, vec![Some(2823)])]);
+    }
+
+    #[test]
+    fn test_multiple_lines() {
+        let path = env::temp_dir().join(format!("day-06-multiple-lines-{}.txt", process::id()));
+        let content = "bvwbjplbgvbhsrlpgdmjqwftvncz\n\
+                       nppdvjthqldpwncqszvftbrmjlhg\n\
+                       \n\
+                       aaaaaaaa\n\
+                       \n\
+                       nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg\n";
+        fs::write(&path, content).expect("Should write file");
+
+        let result = get_first_marker_indices(path.to_str().expect(""), &[4, 14]);
+        fs::remove_file(&path).expect("Should remove file");
+
+        assert_eq!(
+            result,
+            vec![
+                (1, vec![Some(5), Some(23)]),
+                (2, vec![Some(6), Some(23)]),
+                (4, vec![None, None]),
+                (6, vec![Some(10), Some(29)])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_all_markers() {
+        assert_eq!(find_markers("abcdab", 4, true), vec![4, 5, 6]);
+        assert_eq!(find_markers("abcdabcd", 4, false), vec![4, 8]);
+        assert_eq!(find_markers("abcabcd", 4, false), vec![7]);
+        assert_eq!(find_markers("aaaa", 2, true), vec![]);
+        assert_eq!(find_first_marker("", 4), None);
+    }
+
+    /// The original implementation, sorting and deduplicating every window.
+    fn find_first_marker_sorted(line: &str, window: usize) -> Option<usize> {
+        let chars = line.chars().collect::<Vec<char>>();
+        for (i, c) in chars.windows(window).enumerate() {
+            let mut x = c.to_vec();
+            x.sort();
+            x.dedup();
+            if x.len() == window {
+                return Some(i + window);
+            }
+        }
+
+        None
+    }
+
+    /// A stream of `length` characters from an alphabet of `alphabet` letters,
+    /// repeating often enough that markers of most sizes are rare.
+    fn generate_stream(length: usize, alphabet: u32) -> String {
+        let mut state: u64 = 0x2545F4914F6CDD1D;
+        (0..length)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                char::from_u32('a' as u32 + (state % alphabet as u64) as u32).expect("a letter")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_sorted_windows() {
+        for alphabet in [3, 8, 26] {
+            let stream = generate_stream(5_000, alphabet);
+            for window in 1..=12 {
+                assert_eq!(
+                    find_first_marker(&stream, window),
+                    find_first_marker_sorted(&stream, window),
+                    "alphabet {} window {}",
+                    alphabet,
+                    window
+                );
+            }
+        }
+    }
+
+    /// Hands out at most `chunk` bytes per read, like a slow pipe.
+    struct ChunkedReader<'a> {
+        bytes: &'a [u8],
+        chunk: usize,
+    }
+
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            let count = self.chunk.min(buffer.len()).min(self.bytes.len());
+            buffer[..count].copy_from_slice(&self.bytes[..count]);
+            self.bytes = &self.bytes[count..];
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn test_stream_sample() {
+        let file = File::open(TEST_PATH).expect("File should exist");
+        let first = MarkerStream::new(file, 4).next().expect("a marker");
+        assert_eq!(first.expect("readable file"), 7);
+    }
+
+    #[test]
+    fn test_stream_chunks() {
+        let stream = generate_stream(20_000, 12);
+        for chunk in [1, 3, 4096, 10_000] {
+            let reader = ChunkedReader {
+                bytes: stream.as_bytes(),
+                chunk,
+            };
+            let offsets = MarkerStream::new(reader, 8)
+                .collect::<io::Result<Vec<usize>>>()
+                .expect("readable stream");
+
+            let expected = (8..=stream.len())
+                .filter(|end| find_first_marker(&stream[end - 8..*end], 8) == Some(8))
+                .collect::<Vec<usize>>();
+            assert!(!expected.is_empty());
+            assert_eq!(offsets, expected);
+        }
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with cargo test -r -- --ignored --nocapture"]
+    fn bench_multi_megabyte_stream() {
+        let stream = generate_stream(8 * 1024 * 1024, 20);
+        for window in [4, 14, 20] {
+            let start = Instant::now();
+            let sliding = find_first_marker(&stream, window);
+            let sliding_time = start.elapsed();
+
+            let start = Instant::now();
+            let sorted = find_first_marker_sorted(&stream, window);
+            let sorted_time = start.elapsed();
+
+            assert_eq!(sliding, sorted);
+            println!(
+                "window {:>2}: sliding {:?}, sorted {:?}, marker at {:?}",
+                window, sliding_time, sorted_time, sliding
+            );
+        }
     }
 }
@@ -0,0 +1,296 @@
+use crate::filesystem::{FileSystem, NodeId};
+use std::fmt;
+
+/// The size of the disk and how much of it has to be free.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskConfig {
+    pub capacity: usize,
+    pub required_free: usize,
+}
+
+impl Default for DiskConfig {
+    fn default() -> Self {
+        DiskConfig {
+            capacity: 70000000,
+            required_free: 30000000,
+        }
+    }
+}
+
+impl DiskConfig {
+    /// How much has to be deleted, which is zero if enough space is already free. A
+    /// requirement too large to add up saturates, so it can never be met.
+    pub fn get_needed_space(&self, used_space: usize) -> usize {
+        used_space
+            .saturating_add(self.required_free)
+            .saturating_sub(self.capacity)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Delete the single smallest directory that frees enough space.
+    SmallestDirectory,
+    /// Delete as few directories as possible, other than `/`.
+    FewestDirectories,
+    /// Delete as few files as possible, keeping all directories.
+    FewestFiles,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cleanup {
+    pub deleted: Vec<NodeId>,
+    pub freed_space: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CleanupError {
+    NotEnoughSpace { needed: usize, available: usize },
+}
+
+impl fmt::Display for CleanupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CleanupError::NotEnoughSpace { needed, available } => write!(
+                f,
+                "{} has to be freed, but the policy can free at most {}",
+                needed, available
+            ),
+        }
+    }
+}
+
+/// Picks what to delete so that the disk has the required free space.
+pub fn plan_cleanup(
+    file_system: &FileSystem,
+    config: &DiskConfig,
+    policy: Policy,
+) -> Result<Cleanup, CleanupError> {
+    let sizes = file_system.sizes();
+    let needed = config.get_needed_space(sizes[file_system.root()]);
+    if needed == 0 {
+        return Ok(Cleanup {
+            deleted: vec![],
+            freed_space: 0,
+        });
+    }
+
+    let deleted = match policy {
+        Policy::SmallestDirectory => file_system
+            .directories()
+            .into_iter()
+            .filter(|id| sizes[*id] >= needed)
+            .min_by_key(|id| sizes[*id])
+            .map(|id| vec![id]),
+        Policy::FewestDirectories => {
+            let root = file_system.root();
+            let candidates = file_system
+                .directories()
+                .into_iter()
+                .filter(|id| *id != root)
+                .collect::<Vec<NodeId>>();
+            get_fewest(file_system, &sizes, candidates, needed)
+        }
+        Policy::FewestFiles => get_fewest(file_system, &sizes, file_system.files(), needed),
+    };
+
+    match deleted {
+        Some(deleted) => Ok(Cleanup {
+            freed_space: deleted.iter().map(|id| sizes[*id]).sum(),
+            deleted,
+        }),
+        None => {
+            let available = match policy {
+                Policy::SmallestDirectory => sizes[file_system.root()],
+                _ => get_disjoint_total(file_system, &sizes, policy),
+            };
+
+            Err(CleanupError::NotEnoughSpace { needed, available })
+        }
+    }
+}
+
+/// The fewest of `candidates`, none inside another, that add up to at least `needed`.
+///
+/// Only the outermost candidates have to be considered to reach the fewest, so they are
+/// taken biggest first. The last one is then swapped for the smallest candidate that still
+/// covers what remains, to avoid deleting more than necessary.
+fn get_fewest(
+    file_system: &FileSystem,
+    sizes: &[usize],
+    candidates: Vec<NodeId>,
+    needed: usize,
+) -> Option<Vec<NodeId>> {
+    let mut outermost = candidates
+        .iter()
+        .cloned()
+        .filter(|id| {
+            !candidates
+                .iter()
+                .any(|other| is_inside(file_system, *id, *other))
+        })
+        .collect::<Vec<NodeId>>();
+    outermost.sort_by_key(|id| std::cmp::Reverse(sizes[*id]));
+
+    let mut deleted = vec![];
+    let mut freed = 0;
+    for id in outermost {
+        if freed >= needed {
+            break;
+        }
+
+        deleted.push(id);
+        freed += sizes[id];
+    }
+
+    if freed < needed {
+        return None;
+    }
+
+    if let Some(last) = deleted.pop() {
+        let remaining = needed - (freed - sizes[last]);
+        let replacement = candidates
+            .into_iter()
+            .filter(|id| sizes[*id] >= remaining && !deleted.contains(id))
+            .filter(|id| {
+                !deleted
+                    .iter()
+                    .any(|other| is_inside(file_system, *id, *other))
+            })
+            .min_by_key(|id| sizes[*id])
+            .unwrap_or(last);
+        deleted.push(replacement);
+    }
+
+    Some(deleted)
+}
+
+fn get_disjoint_total(file_system: &FileSystem, sizes: &[usize], policy: Policy) -> usize {
+    match policy {
+        Policy::FewestFiles => file_system.files().iter().map(|id| sizes[*id]).sum(),
+        _ => file_system
+            .children(file_system.root())
+            .into_iter()
+            .filter(|id| file_system.is_directory(*id))
+            .map(|id| sizes[id])
+            .sum(),
+    }
+}
+
+/// Whether `id` is a descendant of `ancestor`.
+fn is_inside(file_system: &FileSystem, id: NodeId, ancestor: NodeId) -> bool {
+    let mut current = file_system.node(id).parent;
+    while let Some(parent) = current {
+        if parent == ancestor {
+            return true;
+        }
+
+        current = file_system.node(parent).parent;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::read_sample;
+
+    fn get_paths(file_system: &FileSystem, cleanup: &Cleanup) -> Vec<String> {
+        cleanup
+            .deleted
+            .iter()
+            .map(|id| file_system.path(*id))
+            .collect()
+    }
+
+    #[test]
+    fn test_policies() {
+        let file_system = read_sample();
+        let config = DiskConfig::default();
+
+        let cleanup = plan_cleanup(&file_system, &config, Policy::SmallestDirectory).unwrap();
+        assert_eq!(get_paths(&file_system, &cleanup), vec!["/d"]);
+        assert_eq!(cleanup.freed_space, 24933642);
+
+        let cleanup = plan_cleanup(&file_system, &config, Policy::FewestDirectories).unwrap();
+        assert_eq!(get_paths(&file_system, &cleanup), vec!["/d"]);
+
+        let cleanup = plan_cleanup(&file_system, &config, Policy::FewestFiles).unwrap();
+        assert_eq!(get_paths(&file_system, &cleanup), vec!["/c.dat"]);
+        assert_eq!(cleanup.freed_space, 8504156);
+    }
+
+    #[test]
+    fn test_enough_free_space() {
+        let file_system = read_sample();
+        let config = DiskConfig {
+            capacity: 100000000,
+            required_free: 30000000,
+        };
+
+        let cleanup = plan_cleanup(&file_system, &config, Policy::SmallestDirectory).unwrap();
+        assert_eq!(cleanup.deleted, vec![]);
+        assert_eq!(cleanup.freed_space, 0);
+    }
+
+    #[test]
+    fn test_not_enough_space() {
+        let file_system = read_sample();
+        let config = DiskConfig {
+            capacity: 50000000,
+            required_free: 49000000,
+        };
+
+        let cleanup = plan_cleanup(&file_system, &config, Policy::SmallestDirectory).unwrap();
+        assert_eq!(get_paths(&file_system, &cleanup), vec!["/"]);
+        assert_eq!(
+            plan_cleanup(&file_system, &config, Policy::FewestDirectories),
+            Err(CleanupError::NotEnoughSpace {
+                needed: 47381165,
+                available: 25028495
+            })
+        );
+
+        let config = DiskConfig {
+            capacity: 10,
+            required_free: 20,
+        };
+        assert_eq!(
+            plan_cleanup(&file_system, &config, Policy::SmallestDirectory),
+            Err(CleanupError::NotEnoughSpace {
+                needed: 48381175,
+                available: 48381165
+            })
+        );
+
+        let config = DiskConfig {
+            capacity: 70000000,
+            required_free: usize::MAX,
+        };
+        assert_eq!(config.get_needed_space(48381165), usize::MAX - 70000000);
+        assert!(plan_cleanup(&file_system, &config, Policy::SmallestDirectory).is_err());
+    }
+
+    #[test]
+    fn test_fewest_directories() {
+        let file_system = FileSystem::from_transcript(
+            "$ cd /\n$ ls\ndir a\ndir b\ndir c\n$ cd a\n$ ls\n50 x\ndir small\n\
+             $ cd small\n$ ls\n25 y\n$ cd /\n$ cd b\n$ ls\n40 z\n$ cd /\n$ cd c\n$ ls\n30 w\n",
+        );
+        let config = DiskConfig {
+            capacity: 200,
+            required_free: 150,
+        };
+
+        let cleanup = plan_cleanup(&file_system, &config, Policy::FewestDirectories).unwrap();
+        assert_eq!(get_paths(&file_system, &cleanup), vec!["/a", "/c"]);
+        assert_eq!(cleanup.freed_space, 105);
+
+        let cleanup = plan_cleanup(&file_system, &config, Policy::FewestFiles).unwrap();
+        assert_eq!(
+            get_paths(&file_system, &cleanup),
+            vec!["/a/x", "/b/z", "/a/small/y"]
+        );
+    }
+}
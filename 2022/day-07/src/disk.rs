@@ -0,0 +1,169 @@
+use crate::filesystem::{FileSystem, NodeId, NodeKind};
+use crate::transcript;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+/// Creates the directories and files of `file_system` inside `root`. Files are sparse,
+/// so they report their size without taking up that much space on disk. Names that would
+/// leave `root`, such as `..` or ones containing `/`, are rejected before anything is created.
+pub fn materialise(file_system: &FileSystem, root: &Path) -> io::Result<()> {
+    if let Some(name) = file_system
+        .directories()
+        .into_iter()
+        .chain(file_system.files())
+        .filter(|id| *id != file_system.root())
+        .map(|id| &file_system.node(id).name)
+        .find(|name| !transcript::is_valid_name(name))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{:?} cannot be the name of a file or directory", name),
+        ));
+    }
+
+    fs::create_dir_all(root)?;
+    materialise_children(file_system, file_system.root(), root)
+}
+
+fn materialise_children(file_system: &FileSystem, id: NodeId, directory: &Path) -> io::Result<()> {
+    for child in file_system.children(id) {
+        let node = file_system.node(child);
+        let path = directory.join(&node.name);
+        match node.kind {
+            NodeKind::Directory { .. } => {
+                fs::create_dir_all(&path)?;
+                materialise_children(file_system, child, &path)?;
+            }
+            NodeKind::File { size } => File::create(&path)?.set_len(size as u64)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the directories and regular files inside `root`, using the apparent size of files.
+/// Symbolic links and other special files are left out, and so are names that cannot be
+/// written in a transcript because they contain whitespace.
+pub fn read_directory(root: &Path) -> io::Result<FileSystem> {
+    let mut file_system = FileSystem::new();
+    let id = file_system.root();
+    read_children(&mut file_system, id, root)?;
+    Ok(file_system)
+}
+
+fn read_children(file_system: &mut FileSystem, id: NodeId, directory: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+
+        if name.contains(char::is_whitespace) {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let child = file_system.add_directory(id, &name);
+            read_children(file_system, child, &entry.path())?;
+        } else if file_type.is_file() {
+            file_system.add_file(id, &name, metadata.len() as usize);
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the `$ cd` and `$ ls` transcript that explores the whole tree, in the puzzle's format.
+pub fn to_transcript(file_system: &FileSystem) -> String {
+    let mut lines = vec!["$ cd /".to_string()];
+    write_directory(file_system, file_system.root(), &mut lines);
+    lines.join("\n") + "\n"
+}
+
+fn write_directory(file_system: &FileSystem, id: NodeId, lines: &mut Vec<String>) {
+    lines.push("$ ls".to_string());
+    let children = file_system.children(id);
+    for child in &children {
+        let node = file_system.node(*child);
+        match node.kind {
+            NodeKind::Directory { .. } => lines.push(format!("dir {}", node.name)),
+            NodeKind::File { size } => lines.push(format!("{} {}", size, node.name)),
+        }
+    }
+
+    for child in children
+        .into_iter()
+        .filter(|x| file_system.is_directory(*x))
+    {
+        lines.push(format!("$ cd {}", file_system.node(child).name));
+        write_directory(file_system, child, lines);
+        lines.push("$ cd ..".to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::read_sample;
+    use std::env;
+    use std::process;
+
+    fn get_sizes_by_path(file_system: &FileSystem) -> Vec<(String, usize)> {
+        let sizes = file_system.sizes();
+        let mut paths = (0..sizes.len())
+            .map(|id| (file_system.path(id), sizes[id]))
+            .collect::<Vec<(String, usize)>>();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_transcript_round_trip() {
+        let file_system = read_sample();
+        let transcript = to_transcript(&file_system);
+        assert_eq!(
+            get_sizes_by_path(&FileSystem::from_transcript(&transcript)),
+            get_sizes_by_path(&file_system)
+        );
+        assert_eq!(
+            to_transcript(&FileSystem::from_transcript(&transcript)),
+            transcript
+        );
+    }
+
+    #[test]
+    fn test_materialise_and_read() {
+        let root = env::temp_dir().join(format!("day-07-disk-{}", process::id()));
+        let file_system = read_sample();
+
+        materialise(&file_system, &root).expect("Should create files");
+        let read = read_directory(&root);
+        fs::remove_dir_all(&root).expect("Should remove files");
+
+        let read = read.expect("Should read files");
+        assert_eq!(get_sizes_by_path(&read), get_sizes_by_path(&file_system));
+        assert_eq!(read.sizes()[read.root()], 48381165);
+    }
+
+    #[test]
+    fn test_materialise_rejects_escaping_names() {
+        let base = env::temp_dir().join(format!("day-07-escape-{}", process::id()));
+        let root = base.join("root");
+
+        for name in ["..", ".", "../escaped.txt", "/tmp/escaped.txt"] {
+            let mut file_system = FileSystem::new();
+            let id = file_system.root();
+            file_system.add_file(id, name, 5);
+
+            let error = materialise(&file_system, &root).expect_err("should reject the name");
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+
+        let exists = base.exists();
+        let _ = fs::remove_dir_all(&base);
+        assert!(!exists);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::transcript;
+use std::collections::BTreeMap;
+
+/// Identifies a directory or file within a [`FileSystem`].
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    Directory { children: BTreeMap<String, NodeId> },
+    File { size: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub kind: NodeKind,
+}
+
+/// A tree of directories and files, rooted at `/`.
+///
+/// Nodes are stored in creation order, and a node is always created after its parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileSystem {
+    pub fn new() -> Self {
+        FileSystem {
+            nodes: vec![Node {
+                name: "/".to_string(),
+                parent: None,
+                kind: NodeKind::Directory {
+                    children: BTreeMap::new(),
+                },
+            }],
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        0
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn is_directory(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id].kind, NodeKind::Directory { .. })
+    }
+
+    /// The directories and files directly inside `id`, ordered by name.
+    pub fn children(&self, id: NodeId) -> Vec<NodeId> {
+        match &self.nodes[id].kind {
+            NodeKind::Directory { children } => children.values().cloned().collect(),
+            NodeKind::File { .. } => vec![],
+        }
+    }
+
+    pub fn child(&self, id: NodeId, name: &str) -> Option<NodeId> {
+        match &self.nodes[id].kind {
+            NodeKind::Directory { children } => children.get(name).cloned(),
+            NodeKind::File { .. } => None,
+        }
+    }
+
+    /// Adds a directory to `parent`, or returns the existing one with that name.
+    pub fn add_directory(&mut self, parent: NodeId, name: &str) -> NodeId {
+        self.add_node(
+            parent,
+            name,
+            NodeKind::Directory {
+                children: BTreeMap::new(),
+            },
+        )
+    }
+
+    /// Adds a file to `parent`, or updates the size of the existing one with that name.
+    pub fn add_file(&mut self, parent: NodeId, name: &str, size: usize) -> NodeId {
+        let id = self.add_node(parent, name, NodeKind::File { size });
+        if let NodeKind::File { size: existing } = &mut self.nodes[id].kind {
+            *existing = size;
+        }
+
+        id
+    }
+
+    fn add_node(&mut self, parent: NodeId, name: &str, kind: NodeKind) -> NodeId {
+        if let Some(id) = self.child(parent, name) {
+            return id;
+        }
+
+        let id = self.nodes.len();
+        match &mut self.nodes[parent].kind {
+            NodeKind::Directory { children } => children.insert(name.to_string(), id),
+            NodeKind::File { .. } => panic!("{} is not a directory", self.path(parent)),
+        };
+
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(parent),
+            kind,
+        });
+
+        id
+    }
+
+    /// The absolute path of a node, such as `/a/e` or `/`.
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = vec![];
+        let mut current = id;
+        while let Some(parent) = self.nodes[current].parent {
+            names.push(self.nodes[current].name.as_str());
+            current = parent;
+        }
+
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    /// The size of every node, indexed by [`NodeId`], where a directory's size includes
+    /// everything inside it. Children always come after their parent, so a single pass
+    /// from the last node to the first visits every node after all of its descendants.
+    pub fn sizes(&self) -> Vec<usize> {
+        let mut sizes = self
+            .nodes
+            .iter()
+            .map(|node| match node.kind {
+                NodeKind::File { size } => size,
+                NodeKind::Directory { .. } => 0,
+            })
+            .collect::<Vec<usize>>();
+
+        for (id, node) in self.nodes.iter().enumerate().rev() {
+            if let Some(parent) = node.parent {
+                sizes[parent] += sizes[id];
+            }
+        }
+
+        sizes
+    }
+
+    pub fn directories(&self) -> Vec<NodeId> {
+        (0..self.nodes.len())
+            .filter(|id| self.is_directory(*id))
+            .collect()
+    }
+
+    pub fn files(&self) -> Vec<NodeId> {
+        (0..self.nodes.len())
+            .filter(|id| !self.is_directory(*id))
+            .collect()
+    }
+
+    /// Builds the tree from a terminal transcript, panicking if it cannot be replayed.
+    /// Use [`transcript::parse`] to handle errors and see skipped lines instead.
+    pub fn from_transcript(transcript: &str) -> Self {
+        transcript::parse(transcript)
+            .unwrap_or_else(|error| panic!("{}", error))
+            .file_system
+    }
+}
+
+/// The example transcript from the puzzle statement, for tests across the crate.
+#[cfg(test)]
+pub(crate) fn read_sample() -> FileSystem {
+    let content = std::fs::read_to_string("sample.txt").expect("File should exist");
+    FileSystem::from_transcript(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_size(file_system: &FileSystem, path: &str) -> usize {
+        let sizes = file_system.sizes();
+        let id = (0..sizes.len())
+            .find(|id| file_system.path(*id) == path)
+            .expect("path should exist");
+
+        sizes[id]
+    }
+
+    #[test]
+    fn test_similar_names() {
+        let file_system = FileSystem::from_transcript(
+            "$ cd /\n$ ls\ndir a\ndir ab\n$ cd a\n$ ls\n10 x\n$ cd ..\n$ cd ab\n$ ls\n20 x\n",
+        );
+
+        assert_eq!(get_size(&file_system, "/a"), 10);
+        assert_eq!(get_size(&file_system, "/ab"), 20);
+        assert_eq!(get_size(&file_system, "/"), 30);
+    }
+
+    #[test]
+    fn test_repeated_listing() {
+        let file_system = FileSystem::from_transcript(
+            "$ cd /\n$ ls\n10 x\ndir a\n$ ls\n10 x\ndir a\n$ cd a\n$ ls\n5 y\n$ cd /\n$ ls\n10 x\n",
+        );
+
+        assert_eq!(get_size(&file_system, "/"), 15);
+        assert_eq!(file_system.files().len(), 2);
+        assert_eq!(file_system.directories().len(), 2);
+    }
+
+    #[test]
+    fn test_files_are_kept() {
+        let file_system =
+            FileSystem::from_transcript("$ cd /\n$ ls\n14848514 b.txt\n8504156 c.dat\n");
+        let names = file_system
+            .children(file_system.root())
+            .into_iter()
+            .map(|id| (file_system.path(id), file_system.sizes()[id]))
+            .collect::<Vec<(String, usize)>>();
+
+        assert_eq!(
+            names,
+            vec![
+                ("/b.txt".to_string(), 14848514),
+                ("/c.dat".to_string(), 8504156)
+            ]
+        );
+    }
+}
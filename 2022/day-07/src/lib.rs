@@ -0,0 +1,5 @@
+pub mod cleanup;
+pub mod disk;
+pub mod filesystem;
+pub mod render;
+pub mod transcript;
@@ -1,121 +1,156 @@
-use std::env;
+use day_07::cleanup::{self, CleanupError, DiskConfig, Policy};
+use day_07::disk;
+use day_07::filesystem::FileSystem;
+use day_07::render::{self, SortOrder};
+use day_07::transcript;
 use std::fs;
+use std::path::Path;
+use std::{env, process};
 
 const INPUT_PATH: &str = "input.txt";
+const DEFAULT_THRESHOLD: usize = 100000;
 
 fn main() {
-    let mut args = env::args().skip(1);
+    let mut args = env::args().skip(1).peekable();
     let filepath = args.next().unwrap_or(INPUT_PATH.to_string());
 
-    let totals = get_count(&filepath);
+    match args.peek().map(String::as_str) {
+        Some("tree") => {
+            args.next();
+            let mut max_depth = None;
+            for arg in args {
+                match arg.split_once('=') {
+                    Some(("--max-depth", depth)) => {
+                        max_depth = Some(depth.parse().expect("a depth"))
+                    }
+                    _ => panic!("Expected --max-depth=N, got {}", arg),
+                }
+            }
+
+            println!(
+                "{}",
+                render::render_tree(&get_file_system(&filepath), max_depth)
+            );
+            return;
+        }
+        Some("materialise") => {
+            args.next();
+            let directory = args.next().expect("a directory to create the files in");
+            disk::materialise(&get_file_system(&filepath), Path::new(&directory))
+                .expect("Should create files");
+            return;
+        }
+        Some("transcript") => {
+            let file_system =
+                disk::read_directory(Path::new(&filepath)).expect("Should read directory");
+            print!("{}", disk::to_transcript(&file_system));
+            return;
+        }
+        Some("du") => {
+            args.next();
+            let mut order = SortOrder::Name;
+            let mut max_depth = None;
+            for arg in args {
+                match arg.split_once('=') {
+                    Some(("--sort", "name")) => order = SortOrder::Name,
+                    Some(("--sort", "size")) => order = SortOrder::Size,
+                    Some(("--max-depth", depth)) => {
+                        max_depth = Some(depth.parse().expect("a depth"))
+                    }
+                    _ => panic!("Expected --sort=name|size or --max-depth=N, got {}", arg),
+                }
+            }
+
+            let file_system = get_file_system(&filepath);
+            println!("{}", render::render_usage(&file_system, order, max_depth));
+            return;
+        }
+        _ => {}
+    }
+
+    let mut config = DiskConfig::default();
+    let mut threshold = DEFAULT_THRESHOLD;
+    let mut policy = Policy::SmallestDirectory;
+    for arg in args {
+        let parse = |value: &str| value.parse::<usize>().expect("a size");
+        match arg.split_once('=') {
+            Some(("--capacity", value)) => config.capacity = parse(value),
+            Some(("--required", value)) => config.required_free = parse(value),
+            Some(("--threshold", value)) => threshold = parse(value),
+            Some(("--policy", "smallest")) => policy = Policy::SmallestDirectory,
+            Some(("--policy", "fewest")) => policy = Policy::FewestDirectories,
+            Some(("--policy", "files")) => policy = Policy::FewestFiles,
+            _ => panic!(
+                "Expected --capacity=N, --required=N, --threshold=N \
+                 or --policy=smallest|fewest|files, got {}",
+                arg
+            ),
+        }
+    }
+
+    let totals = get_count(&filepath, threshold);
     println!("Count: {:?}", totals);
 
-    let deleted_size = get_deleted_directory_size(&filepath);
-    println!("Deleted size: {:?}", deleted_size);
+    match get_cleanup(&filepath, &config, policy) {
+        Ok((deleted_size, paths)) => {
+            println!("Deleted size: {:?}", deleted_size);
+            for path in paths {
+                println!("Deleted: {}", path);
+            }
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
 }
 
-fn get_count(path: &str) -> usize {
-    let sizes = get_directory_sizes(path);
-
-    sizes
+/// The sum of the sizes of all directories of at most `threshold` size.
+fn get_count(path: &str, threshold: usize) -> usize {
+    get_directory_sizes(path)
         .iter()
-        .map(|dir| {
-            sizes
-                .iter()
-                .filter(|x| x.0.starts_with(&dir.0))
-                .map(|x| x.1)
-                .sum::<usize>()
-        })
-        .filter(|x| x <= &100000)
+        .map(|dir| dir.1)
+        .filter(|x| x <= &threshold)
         .sum::<usize>()
 }
 
-fn get_deleted_directory_size(path: &str) -> usize {
-    let sizes = get_directory_sizes(path);
-
-    let total_size: usize = 70000000;
-    let needed_space: usize = 30000000;
-    let used_space: usize = sizes.iter().map(|x| x.1).sum();
-    let free_space = total_size - used_space;
-    let minimum_deleted = needed_space - free_space;
-
-    let mut dirs = sizes
+/// The space freed by following `policy`, and the paths it deletes.
+fn get_cleanup(
+    path: &str,
+    config: &DiskConfig,
+    policy: Policy,
+) -> Result<(usize, Vec<String>), CleanupError> {
+    let file_system = get_file_system(path);
+    let cleanup = cleanup::plan_cleanup(&file_system, config, policy)?;
+    let paths = cleanup
+        .deleted
         .iter()
-        .map(|dir| {
-            sizes
-                .iter()
-                .filter(|x| x.0.starts_with(&dir.0))
-                .map(|x| x.1)
-                .sum::<usize>()
-        })
-        .filter(|x| x >= &minimum_deleted)
-        .collect::<Vec<usize>>();
-
-    dirs.sort();
-
-    dirs[0]
+        .map(|id| file_system.path(*id))
+        .collect();
+
+    Ok((cleanup.freed_space, paths))
 }
 
+/// The path and total size, including subdirectories, of every directory, starting with `/`.
 fn get_directory_sizes(path: &str) -> Vec<(String, usize)> {
-    let content = fs::read_to_string(&path).expect("File should exist");
-    let lines = content.lines().rev().collect::<Vec<&str>>();
-    let x = lines
-        .split_inclusive(|line| line.chars().next() == Some('$'))
-        .rev()
-        .map(|x| x.into_iter().rev().cloned().collect::<Vec<&str>>())
-        .collect::<Vec<Vec<&str>>>();
-
-    let mut current_dir = "".to_string();
-    let mut sizes: Vec<(String, usize)> = vec![];
-
-    for commands in x.into_iter() {
-        let mut iter = commands.into_iter();
-        let cmd = iter
-            .next()
-            .expect("Should contain single line")
-            .split(" ")
-            .collect::<Vec<&str>>();
-
-        match cmd[..] {
-            ["$", "ls"] => {
-                let size = iter
-                    .filter(|line| !line.starts_with("dir"))
-                    .map(|line| {
-                        line.split(" ")
-                            .next()
-                            .expect("")
-                            .parse::<usize>()
-                            .expect("")
-                    })
-                    .sum::<usize>();
-
-                sizes.push((current_dir.clone(), size));
-            }
-            ["$", "cd", x] => match x {
-                ".." => {
-                    let index = current_dir
-                        .rfind("/")
-                        .expect("should contain directory separator");
-
-                    if index == 0 {
-                        current_dir = "/".to_string();
-                    } else {
-                        current_dir = current_dir[0..index].to_string();
-                    }
-                }
-                new_dir => {
-                    if current_dir.ends_with("/") || new_dir.starts_with("/") {
-                        current_dir = current_dir + new_dir;
-                    } else {
-                        current_dir = current_dir + "/" + new_dir;
-                    }
-                }
-            },
-            _ => panic!("Unexpected command"),
-        }
+    let file_system = get_file_system(path);
+    let sizes = file_system.sizes();
+
+    file_system
+        .directories()
+        .into_iter()
+        .map(|id| (file_system.path(id), sizes[id]))
+        .collect()
+}
+
+fn get_file_system(path: &str) -> FileSystem {
+    let content = fs::read_to_string(path).expect("File should exist");
+    let transcript = transcript::parse(&content).unwrap_or_else(|error| panic!("{}", error));
+    for warning in transcript.warnings {
+        eprintln!("{}", warning);
     }
 
-    sizes
+    transcript.file_system
 }
 
 #[cfg(test)]
@@ -126,25 +161,128 @@ mod tests {
 
     #[test]
     fn test_sample() {
-        let result = get_count(TEST_PATH);
+        let result = get_count(TEST_PATH, DEFAULT_THRESHOLD);
         assert_eq!(result, 95437);
     }
 
     #[test]
     fn test_input() {
-        let result = get_count(INPUT_PATH);
+        let result = get_count(INPUT_PATH, DEFAULT_THRESHOLD);
         assert_eq!(result, 1453349);
     }
 
     #[test]
     fn test_second_sample() {
-        let result = get_deleted_directory_size(TEST_PATH);
-        assert_eq!(result, 24933642);
+        let result = get_cleanup(TEST_PATH, &DiskConfig::default(), Policy::SmallestDirectory);
+        assert_eq!(result, Ok((24933642, vec!["/d".to_string()])));
     }
 
     #[test]
     fn test_second_input() {
-        let result = get_deleted_directory_size(INPUT_PATH);
-        assert_eq!(result, 2948823);
+        let result = get_cleanup(
+            INPUT_PATH,
+            &DiskConfig::default(),
+            Policy::SmallestDirectory,
+        );
+        assert_eq!(result.map(|x| x.0), Ok(2948823));
+    }
+
+    #[test]
+    fn test_materialised_input() {
+        let directory = env::temp_dir().join(format!("day-07-input-{}", process::id()));
+        disk::materialise(&get_file_system(INPUT_PATH), &directory).expect("Should create files");
+
+        let transcript = env::temp_dir().join(format!("day-07-input-{}.txt", process::id()));
+        let read = disk::read_directory(&directory);
+        fs::remove_dir_all(&directory).expect("Should remove files");
+        fs::write(
+            &transcript,
+            disk::to_transcript(&read.expect("Should read files")),
+        )
+        .expect("Should write transcript");
+
+        let path = transcript.to_str().expect("");
+        let count = get_count(path, DEFAULT_THRESHOLD);
+        let cleanup = get_cleanup(path, &DiskConfig::default(), Policy::SmallestDirectory);
+        fs::remove_file(&transcript).expect("Should remove transcript");
+
+        assert_eq!(count, 1453349);
+        assert_eq!(cleanup.map(|x| x.0), Ok(2948823));
+    }
+
+    /// The apparent size of the directory entries below and including `directory`, which
+    /// `du -b` counts on top of the file sizes.
+    #[cfg(target_os = "linux")]
+    fn get_directory_entry_bytes(directory: &Path) -> usize {
+        let own = fs::metadata(directory).expect("Should read metadata").len() as usize;
+        let children = fs::read_dir(directory)
+            .expect("Should read directory")
+            .map(|entry| entry.expect("Should read entry").path())
+            .filter(|path| path.is_dir())
+            .map(|path| get_directory_entry_bytes(&path))
+            .sum::<usize>();
+
+        own + children
+    }
+
+    /// Cross-checks the directory sizes against GNU `du`, which Linux systems provide.
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_materialised_input_du() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let directory = env::temp_dir().join(format!("day-07-du-{}", process::id()));
+        let file_system = get_file_system(INPUT_PATH);
+        disk::materialise(&file_system, &directory).expect("Should create files");
+
+        let sizes = file_system.sizes();
+        let expected = file_system
+            .directories()
+            .into_iter()
+            .map(|id| {
+                let path = directory.join(file_system.path(id).trim_start_matches('/'));
+                let size = sizes[id] + get_directory_entry_bytes(&path);
+                (path, size)
+            })
+            .collect::<Vec<(PathBuf, usize)>>();
+        let output = process::Command::new("du")
+            .arg("-b")
+            .arg(&directory)
+            .output();
+        fs::remove_dir_all(&directory).expect("Should remove files");
+
+        let output = output.expect("du should run");
+        assert!(
+            output.status.success(),
+            "du failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let reported = String::from_utf8(output.stdout)
+            .expect("du should print paths as UTF-8")
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(size, path)| (PathBuf::from(path), size.parse().expect("a size")))
+            .collect::<HashMap<PathBuf, usize>>();
+
+        assert_eq!(reported.len(), expected.len());
+        for (path, size) in expected {
+            assert_eq!(reported.get(&path), Some(&size), "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn test_directory_sizes_sample() {
+        let result = get_directory_sizes(TEST_PATH);
+        assert_eq!(
+            result,
+            vec![
+                ("/".to_string(), 48381165),
+                ("/a".to_string(), 94853),
+                ("/d".to_string(), 24933642),
+                ("/a/e".to_string(), 584)
+            ]
+        );
     }
 }
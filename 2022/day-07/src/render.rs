@@ -0,0 +1,170 @@
+use crate::filesystem::{FileSystem, NodeId, NodeKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Name,
+    Size,
+}
+
+/// Renders the tree the way the puzzle statement shows it, with two spaces of indentation
+/// per level and children in the order the transcript lists them. Levels below `max_depth`
+/// are left out.
+pub fn render_tree(file_system: &FileSystem, max_depth: Option<usize>) -> String {
+    let mut lines = vec![];
+    let mut stack = vec![(file_system.root(), 0)];
+    while let Some((id, depth)) = stack.pop() {
+        let node = file_system.node(id);
+        let description = match node.kind {
+            NodeKind::Directory { .. } => "dir".to_string(),
+            NodeKind::File { size } => format!("file, size={}", size),
+        };
+        lines.push(format!(
+            "{}- {} ({})",
+            "  ".repeat(depth),
+            node.name,
+            description
+        ));
+
+        if max_depth.is_none_or(|max| depth < max) {
+            // Nodes are created in transcript order, so their ids keep that order.
+            let mut children = file_system.children(id);
+            children.sort();
+            for child in children.into_iter().rev() {
+                stack.push((child, depth + 1));
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Renders the total size of every directory like `du -h`. Ordered by name, directories
+/// come after everything inside them; ordered by size, the biggest come first.
+/// Directories more than `max_depth` levels below `/` are left out, but still counted.
+pub fn render_usage(
+    file_system: &FileSystem,
+    order: SortOrder,
+    max_depth: Option<usize>,
+) -> String {
+    let sizes = file_system.sizes();
+    let mut directories = vec![];
+    collect_post_order(file_system, file_system.root(), 0, &mut directories);
+
+    let mut directories = directories
+        .into_iter()
+        .filter(|(_, depth)| max_depth.is_none_or(|max| *depth <= max))
+        .map(|(id, _)| id)
+        .collect::<Vec<NodeId>>();
+
+    if order == SortOrder::Size {
+        directories.sort_by_key(|id| std::cmp::Reverse(sizes[*id]));
+    }
+
+    directories
+        .into_iter()
+        .map(|id| format!("{}\t{}", format_size(sizes[id]), file_system.path(id)))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn collect_post_order(
+    file_system: &FileSystem,
+    id: NodeId,
+    depth: usize,
+    directories: &mut Vec<(NodeId, usize)>,
+) {
+    for child in file_system.children(id) {
+        if file_system.is_directory(child) {
+            collect_post_order(file_system, child, depth + 1, directories);
+        }
+    }
+
+    directories.push((id, depth));
+}
+
+/// Formats a size in bytes like `du -h`: rounded up, with one decimal below 10 and
+/// a K, M, G or T suffix for powers of 1024. The value is rounded before the suffix is
+/// picked, so 1048575 bytes are `1.0M` rather than `1024K`.
+pub fn format_size(size: usize) -> String {
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let suffixes = ['K', 'M', 'G', 'T'];
+    let mut value = size as f64;
+    for (index, suffix) in suffixes.iter().enumerate() {
+        value /= 1024.0;
+        let tenths = (value * 10.0).ceil() / 10.0;
+        let rounded = if tenths < 10.0 { tenths } else { value.ceil() };
+
+        if rounded < 1024.0 || index == suffixes.len() - 1 {
+            return if rounded < 10.0 {
+                format!("{:.1}{}", rounded, suffix)
+            } else {
+                format!("{}{}", rounded, suffix)
+            };
+        }
+    }
+
+    unreachable!("the last suffix always returns")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::read_sample;
+
+    #[test]
+    fn test_render_tree() {
+        let file_system = read_sample();
+        let expected = "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - j (file, size=4060174)
+    - d.log (file, size=8033020)
+    - d.ext (file, size=5626152)
+    - k (file, size=7214296)";
+
+        assert_eq!(render_tree(&file_system, None), expected);
+        assert_eq!(render_tree(&file_system, Some(0)), "- / (dir)");
+        assert_eq!(render_tree(&file_system, Some(2)).lines().count(), 13);
+    }
+
+    #[test]
+    fn test_render_usage() {
+        let file_system = read_sample();
+
+        assert_eq!(
+            render_usage(&file_system, SortOrder::Name, None),
+            "584\t/a/e\n93K\t/a\n24M\t/d\n47M\t/"
+        );
+        assert_eq!(
+            render_usage(&file_system, SortOrder::Size, Some(1)),
+            "47M\t/\n24M\t/d\n93K\t/a"
+        );
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0");
+        assert_eq!(format_size(1023), "1023");
+        assert_eq!(format_size(1024), "1.0K");
+        assert_eq!(format_size(1025), "1.1K");
+        assert_eq!(format_size(10 * 1024), "10K");
+        assert_eq!(format_size(10189), "10K");
+        assert_eq!(format_size(10 * 1024 - 1), "10K");
+        assert_eq!(format_size(1048575), "1.0M");
+        assert_eq!(format_size(1024 * 1024), "1.0M");
+        assert_eq!(format_size((1 << 30) - 1), "1.0G");
+        assert_eq!(format_size(3 << 30), "3.0G");
+        assert_eq!(format_size(2048 << 40), "2048T");
+    }
+}
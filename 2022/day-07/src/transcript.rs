@@ -0,0 +1,293 @@
+use crate::filesystem::{FileSystem, NodeId};
+use std::fmt;
+
+/// A file system rebuilt from a transcript, with notes about the lines that were skipped.
+#[derive(Debug)]
+pub struct Transcript {
+    pub file_system: FileSystem,
+    pub warnings: Vec<String>,
+}
+
+/// Why a transcript could not be replayed, with the one-based line it happened on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranscriptError {
+    UnknownDirectory { line: usize, path: String },
+    NotADirectory { line: usize, path: String },
+    NameConflict { line: usize, path: String },
+    UnexpectedOutput { line: usize },
+    InvalidListing { line: usize, text: String },
+    InvalidName { line: usize, name: String },
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptError::UnknownDirectory { line, path } => {
+                write!(f, "Line {}: directory {} was not listed", line, path)
+            }
+            TranscriptError::NotADirectory { line, path } => {
+                write!(f, "Line {}: {} is not a directory", line, path)
+            }
+            TranscriptError::NameConflict { line, path } => {
+                write!(
+                    f,
+                    "Line {}: {} is listed as both a file and a directory",
+                    line, path
+                )
+            }
+            TranscriptError::UnexpectedOutput { line } => {
+                write!(f, "Line {}: output without a command that prints it", line)
+            }
+            TranscriptError::InvalidListing { line, text } => {
+                write!(
+                    f,
+                    "Line {}: expected dir NAME or SIZE NAME, got {:?}",
+                    line, text
+                )
+            }
+            TranscriptError::InvalidName { line, name } => {
+                write!(
+                    f,
+                    "Line {}: {:?} cannot be the name of a file or directory",
+                    line, name
+                )
+            }
+        }
+    }
+}
+
+/// What the lines after the last command are the output of.
+enum Output {
+    None,
+    Listing,
+    Unknown,
+}
+
+/// Replays the `cd` and `ls` commands of a transcript. `cd` accepts absolute and relative
+/// paths such as `/a/b` or `../..`, listing a directory again does not count it twice, and
+/// other commands are skipped along with their output.
+pub fn parse(transcript: &str) -> Result<Transcript, TranscriptError> {
+    let mut file_system = FileSystem::new();
+    let mut warnings = vec![];
+    let mut current = file_system.root();
+    let mut output = Output::None;
+
+    for (index, text) in transcript.lines().enumerate() {
+        let line = index + 1;
+        let words = text.split_whitespace().collect::<Vec<&str>>();
+        match (&output, &words[..]) {
+            (_, []) => {}
+            (_, ["$", "ls"]) => output = Output::Listing,
+            (_, ["$", "cd"]) => {
+                current = file_system.root();
+                output = Output::None;
+            }
+            (_, ["$", "cd", path]) => {
+                current = change_directory(&file_system, current, path, line)?;
+                output = Output::None;
+            }
+            (_, ["$", ..]) => {
+                warnings.push(format!(
+                    "Line {}: skipping unknown command {:?}",
+                    line, text
+                ));
+                output = Output::Unknown;
+            }
+            (Output::Listing, ["dir", name]) => {
+                check_name(name, line)?;
+                if let Some(existing) = file_system.child(current, name) {
+                    if !file_system.is_directory(existing) {
+                        let path = file_system.path(existing);
+                        return Err(TranscriptError::NameConflict { line, path });
+                    }
+                }
+
+                file_system.add_directory(current, name);
+            }
+            (Output::Listing, [size, name]) => {
+                let size = size
+                    .parse::<usize>()
+                    .map_err(|_| TranscriptError::InvalidListing {
+                        line,
+                        text: text.to_string(),
+                    })?;
+
+                check_name(name, line)?;
+                if let Some(existing) = file_system.child(current, name) {
+                    if file_system.is_directory(existing) {
+                        let path = file_system.path(existing);
+                        return Err(TranscriptError::NameConflict { line, path });
+                    }
+                }
+
+                file_system.add_file(current, name, size);
+            }
+            (Output::Listing, _) => {
+                return Err(TranscriptError::InvalidListing {
+                    line,
+                    text: text.to_string(),
+                })
+            }
+            (Output::Unknown, _) => {
+                warnings.push(format!("Line {}: skipping output {:?}", line, text));
+            }
+            (Output::None, _) => return Err(TranscriptError::UnexpectedOutput { line }),
+        }
+    }
+
+    Ok(Transcript {
+        file_system,
+        warnings,
+    })
+}
+
+/// Listed names have to stay inside their directory, so they cannot contain `/` or be `.` or `..`.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains('/') && name != "." && name != ".."
+}
+
+fn check_name(name: &str, line: usize) -> Result<(), TranscriptError> {
+    match is_valid_name(name) {
+        true => Ok(()),
+        false => Err(TranscriptError::InvalidName {
+            line,
+            name: name.to_string(),
+        }),
+    }
+}
+
+fn change_directory(
+    file_system: &FileSystem,
+    current: NodeId,
+    path: &str,
+    line: usize,
+) -> Result<NodeId, TranscriptError> {
+    let mut directory = if path.starts_with('/') {
+        file_system.root()
+    } else {
+        current
+    };
+
+    for name in path.split('/').filter(|name| !name.is_empty()) {
+        directory = match name {
+            "." => directory,
+            ".." => file_system.node(directory).parent.unwrap_or(directory),
+            name => {
+                let child = file_system.child(directory, name).ok_or_else(|| {
+                    TranscriptError::UnknownDirectory {
+                        line,
+                        path: join(&file_system.path(directory), name),
+                    }
+                })?;
+
+                if !file_system.is_directory(child) {
+                    let path = file_system.path(child);
+                    return Err(TranscriptError::NotADirectory { line, path });
+                }
+
+                child
+            }
+        };
+    }
+
+    Ok(directory)
+}
+
+fn join(directory: &str, name: &str) -> String {
+    match directory {
+        "/" => format!("/{}", name),
+        directory => format!("{}/{}", directory, name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_size(file_system: &FileSystem, path: &str) -> usize {
+        let id = file_system
+            .directories()
+            .into_iter()
+            .find(|id| file_system.path(*id) == path)
+            .expect("path should exist");
+
+        file_system.sizes()[id]
+    }
+
+    #[test]
+    fn test_paths() {
+        let transcript = parse(
+            "$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\ndir b\n$ cd b\n$ ls\ndir c\n1 x\n\
+             $ cd c\n$ ls\n2 y\n$ cd ../..\n$ ls\n4 z\n$ cd /a/b/c\n$ cd /\n\
+             $ cd a/b/./c/../..\n$ ls\n4 z\n",
+        )
+        .expect("valid transcript");
+
+        assert!(transcript.warnings.is_empty());
+        assert_eq!(get_size(&transcript.file_system, "/"), 7);
+        assert_eq!(get_size(&transcript.file_system, "/a/b"), 3);
+    }
+
+    #[test]
+    fn test_unknown_commands() {
+        let transcript =
+            parse("$ cd /\n$ pwd\n/\n$ ls -la\ntotal 1\n$ ls\n10 x\n").expect("valid transcript");
+
+        assert_eq!(transcript.warnings.len(), 4);
+        assert_eq!(
+            transcript.warnings[0],
+            "Line 2: skipping unknown command \"$ pwd\""
+        );
+        assert_eq!(get_size(&transcript.file_system, "/"), 10);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse("$ cd /\n$ ls\ndir a\n$ cd b\n").unwrap_err(),
+            TranscriptError::UnknownDirectory {
+                line: 4,
+                path: "/b".to_string()
+            }
+        );
+        assert_eq!(
+            parse("$ cd /\n$ ls\n10 a\n$ cd a\n").unwrap_err(),
+            TranscriptError::NotADirectory {
+                line: 4,
+                path: "/a".to_string()
+            }
+        );
+        assert_eq!(
+            parse("$ cd /\n$ ls\n10 a\n$ ls\ndir a\n").unwrap_err(),
+            TranscriptError::NameConflict {
+                line: 5,
+                path: "/a".to_string()
+            }
+        );
+        assert_eq!(
+            parse("$ cd /\n10 a\n").unwrap_err(),
+            TranscriptError::UnexpectedOutput { line: 2 }
+        );
+        assert_eq!(
+            parse("$ ls\nten a\n").unwrap_err(),
+            TranscriptError::InvalidListing {
+                line: 2,
+                text: "ten a".to_string()
+            }
+        );
+        assert_eq!(
+            parse("$ ls\n5 /tmp/escaped.txt\n").unwrap_err(),
+            TranscriptError::InvalidName {
+                line: 2,
+                name: "/tmp/escaped.txt".to_string()
+            }
+        );
+        assert_eq!(
+            parse("$ ls\ndir ..\n").unwrap_err(),
+            TranscriptError::InvalidName {
+                line: 2,
+                name: "..".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod ranking;
+pub mod render;
+pub mod survey;
@@ -1,133 +1,130 @@
-use itertools::Itertools;
+use day_08::ranking::{self, Ties};
+use day_08::render::{self, ImageFormat};
+use day_08::survey::{self, Offset, Survey, CARDINAL};
+use grid::{Grid, GridError};
 use std::env;
-use std::fs;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
 
 const INPUT_PATH: &str = "input.txt";
+const DEFAULT_TOP: usize = 10;
 
 fn main() {
     let mut args = env::args().skip(1);
     let filepath = args.next().unwrap_or(INPUT_PATH.to_string());
 
-    let count = get_count(&filepath);
-    println!("Count: {:?}", count);
-
-    let score = get_scenic_score(&filepath);
-    println!("Score: {:?}", score);
-}
-
-fn get_count(path: &str) -> usize {
-    let content = fs::read_to_string(&path).expect("File should exist");
-    let lines = content.lines().collect::<Vec<&str>>();
-
-    let mut trees: Vec<Vec<u8>> = vec![];
-    for (y, line) in lines.into_iter().enumerate() {
-        trees.push(vec![]);
-
-        for char in line.chars().into_iter() {
-            let height = char.to_digit(10).expect("a digit") as u8;
-            trees[y].push(height);
+    let mut offsets = CARDINAL.to_vec();
+    let mut heights = Heights::Digits;
+    let mut mode_args = vec![];
+    for arg in args {
+        match arg.split_once('=') {
+            Some(("--heights", "digits")) => heights = Heights::Digits,
+            Some(("--heights", "separated")) => heights = Heights::Separated,
+            Some(("--directions", spec)) => {
+                offsets = survey::parse_offsets(spec).unwrap_or_else(|e| panic!("{}", e))
+            }
+            _ => mode_args.push(arg),
         }
     }
 
-    let mut visible: usize = 0;
-
-    for (y, heights) in trees.iter().enumerate() {
-        for (x, height) in heights.iter().enumerate() {
-            if y == 0 || x == 0 || y == trees.len() || x == heights.len() {
-                visible = visible + 1;
-                continue;
-            }
-
-            let mut left = heights[0..x].iter();
-            let mut right = heights[x + 1..heights.len()].iter();
-            let mut top = trees.iter().enumerate().filter(|c| c.0 < y).map(|c| c.1[x]);
-            let mut bottom = trees.iter().enumerate().filter(|c| c.0 > y).map(|c| c.1[x]);
-
-            if left.all(|h| h < height)
-                || right.all(|h| h < height)
-                || top.all(|h| h < *height)
-                || bottom.all(|h| h < *height)
-            {
-                visible = visible + 1;
+    let mut args = mode_args.into_iter();
+    match args.next().as_deref() {
+        Some("visibility") => {
+            let trees = read_trees(&filepath, heights);
+            print!(
+                "{}",
+                render::render_visibility(&Survey::new(&trees, &offsets))
+            );
+            return;
+        }
+        Some("heatmap") => {
+            let trees = read_trees(&filepath, heights);
+            let scores = Survey::new(&trees, &offsets).scores();
+            match args.next() {
+                Some(image_path) => write_heatmap(&scores, Path::new(&image_path)),
+                None => print!("{}", render::render_heatmap(&trees, &scores)),
             }
+            return;
         }
-    }
-
-    visible
-}
-
-fn get_scenic_score(path: &str) -> usize {
-    let content = fs::read_to_string(&path).expect("File should exist");
-    let lines = content.lines().collect::<Vec<&str>>();
-
-    let mut trees: Vec<Vec<u8>> = vec![];
-    for (y, line) in lines.into_iter().enumerate() {
-        trees.push(vec![]);
+        Some("top") => {
+            let mut k = DEFAULT_TOP;
+            let mut ties = Ties::Truncate;
+            for arg in args {
+                match arg.split_once('=') {
+                    Some(("--ties", "truncate")) => ties = Ties::Truncate,
+                    Some(("--ties", "include")) => ties = Ties::Include,
+                    None => k = arg.parse().expect("a number of trees"),
+                    _ => panic!("Expected a count or --ties=truncate|include, got {}", arg),
+                }
+            }
 
-        for char in line.chars().into_iter() {
-            let height = char.to_digit(10).expect("a digit") as u8;
-            trees[y].push(height);
+            let trees = read_trees(&filepath, heights);
+            let survey = Survey::new(&trees, &offsets);
+            for tree in ranking::top_trees(&trees, &survey, k, ties) {
+                println!("{}", tree);
+            }
+            return;
         }
+        Some(mode) => panic!("Expected visibility, heatmap or top, got {}", mode),
+        None => {}
     }
 
-    let mut scores: Vec<usize> = vec![];
+    let count = get_count(&filepath, heights, &offsets);
+    println!("Count: {:?}", count);
 
-    for (y, heights) in trees.iter().enumerate() {
-        for (x, height) in heights.iter().enumerate() {
-            if y == 0 || x == 0 || y == trees.len() - 1 || x == heights.len() - 1 {
-                continue;
-            }
+    let score = get_scenic_score(&filepath, heights, &offsets);
+    println!("Score: {:?}", score);
+}
 
-            let score = calculate_score(x, y, &heights, height, &trees);
-            scores.push(score);
-        }
-    }
+/// How the heights in the input are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Heights {
+    /// One digit per tree, as in the puzzle input.
+    Digits,
+    /// Whole numbers separated by commas, whitespace or both.
+    Separated,
+}
 
-    scores.sort();
-    scores.into_iter().rev().next().expect("should have values")
+fn read_trees(path: &str, heights: Heights) -> Grid<u32> {
+    let content = fs::read_to_string(path).expect("File should exist");
+    parse_trees(&content, heights).unwrap_or_else(|e| panic!("{}", e))
 }
 
-fn calculate_score(
-    x: usize,
-    y: usize,
-    heights: &Vec<u8>,
-    height: &u8,
-    trees: &Vec<Vec<u8>>,
-) -> usize {
-    let mut left_trees_iter = heights[0..x].iter().rev();
-    let mut left = left_trees_iter.by_ref().take_while(|h| h < &height).count();
-    if left_trees_iter.next().is_some() {
-        left = left + 1;
+fn parse_trees(content: &str, heights: Heights) -> Result<Grid<u32>, GridError> {
+    match heights {
+        Heights::Digits => content
+            .parse::<Grid<u8>>()
+            .map(|trees| trees.map(|&height| height as u32)),
+        Heights::Separated => Grid::parse_separated(content),
     }
+}
 
-    let mut right_trees_iter = heights[x + 1..heights.len()].iter();
-    let mut right = right_trees_iter.take_while_ref(|h| h < &height).count();
-    if right_trees_iter.next().is_some() {
-        right = right + 1;
-    }
+fn write_heatmap(scores: &Grid<usize>, path: &Path) {
+    let format = ImageFormat::from_path(path).expect("Image path should end in .pgm or .ppm");
+    let mut writer = BufWriter::new(File::create(path).expect("Should create image"));
+    render::write_image(scores, format, &mut writer).expect("Should write image");
+}
 
-    let mut top_trees_iter = trees
-        .iter()
-        .enumerate()
-        .filter(|c| c.0 < y)
-        .map(|c| c.1[x])
-        .rev();
+fn get_count(path: &str, heights: Heights, offsets: &[Offset]) -> usize {
+    let trees = read_trees(path, heights);
+    let survey = Survey::new(&trees, offsets);
 
-    let mut top = top_trees_iter.by_ref().take_while(|h| h < height).count();
-    if top_trees_iter.next().is_some() {
-        top = top + 1;
-    }
+    trees
+        .positions()
+        .filter(|&position| survey.is_visible(position))
+        .count()
+}
 
-    let mut bottom_trees_iter = trees.iter().enumerate().filter(|c| c.0 > y).map(|c| c.1[x]);
-    let mut bottom = bottom_trees_iter
-        .by_ref()
-        .take_while(|h| h < height)
-        .count();
-    if bottom_trees_iter.next().is_some() {
-        bottom = bottom + 1;
-    }
+fn get_scenic_score(path: &str, heights: Heights, offsets: &[Offset]) -> usize {
+    let trees = read_trees(path, heights);
+    let survey = Survey::new(&trees, offsets);
 
-    (left) * (right) * (top) * (bottom)
+    trees
+        .positions()
+        .map(|position| survey.score(position))
+        .max()
+        .expect("should have values")
 }
 
 #[cfg(test)]
@@ -138,25 +135,56 @@ mod tests {
 
     #[test]
     fn test_sample() {
-        let result = get_count(TEST_PATH);
+        let result = get_count(TEST_PATH, Heights::Digits, &CARDINAL);
         assert_eq!(result, 21);
     }
 
     #[test]
     fn test_input() {
-        let result = get_count(INPUT_PATH);
+        let result = get_count(INPUT_PATH, Heights::Digits, &CARDINAL);
         assert_eq!(result, 1849);
     }
 
     #[test]
     fn test_second_sample() {
-        let result = get_scenic_score(TEST_PATH);
+        let result = get_scenic_score(TEST_PATH, Heights::Digits, &CARDINAL);
         assert_eq!(result, 8);
     }
 
     #[test]
     fn test_second_input() {
-        let result = get_scenic_score(INPUT_PATH);
+        let result = get_scenic_score(INPUT_PATH, Heights::Digits, &CARDINAL);
         assert_eq!(result, 201600);
     }
+
+    #[test]
+    fn test_diagonal_sample() {
+        let offsets = survey::parse_offsets("cardinal+diagonal").unwrap();
+        assert_eq!(get_count(TEST_PATH, Heights::Digits, &offsets), 22);
+        assert_eq!(get_scenic_score(TEST_PATH, Heights::Digits, &offsets), 16);
+    }
+
+    #[test]
+    fn test_separated_heights() {
+        let digits = parse_trees("30373\n25512\n", Heights::Digits).unwrap();
+        let commas = parse_trees("3,0,3,7,3\n2,5,5,1,2\n", Heights::Separated).unwrap();
+        let spaces = parse_trees("3 0 3  7 3\n2 5 5 1 2\n", Heights::Separated).unwrap();
+        assert_eq!(commas, digits);
+        assert_eq!(spaces, digits);
+
+        let tall = parse_trees("10, 200, 30\n40, 50, 60\n", Heights::Separated).unwrap();
+        assert_eq!(tall.row(0), &[10, 200, 30]);
+        assert!(parse_trees("1,2\n3,x\n", Heights::Separated).is_err());
+        assert!(parse_trees("1,2\n3,4\n", Heights::Digits).is_err());
+    }
+
+    #[test]
+    fn test_single_column_heights() {
+        let tall = parse_trees("10\n20\n", Heights::Separated).unwrap();
+        assert_eq!((tall.width(), tall.height()), (1, 2));
+        assert_eq!(tall.column(0).copied().collect::<Vec<u32>>(), vec![10, 20]);
+
+        let digits = parse_trees("10\n20\n", Heights::Digits).unwrap();
+        assert_eq!((digits.width(), digits.height()), (2, 2));
+    }
 }
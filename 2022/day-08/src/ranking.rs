@@ -0,0 +1,142 @@
+use crate::survey::{self, Offset, Survey};
+use grid::{Grid, Position};
+use std::cmp::Reverse;
+use std::fmt;
+
+/// What to do with trees that score the same as the last of the `k` best.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ties {
+    /// Stop at exactly `k` trees, keeping the tied ones that come first in reading order.
+    Truncate,
+    /// Keep every tree tied with the `k`-th, so more than `k` trees may be returned.
+    Include,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankedTree<T> {
+    /// The competition rank: trees with the same score share a rank and the next rank is skipped.
+    pub rank: usize,
+    pub position: Position,
+    pub height: T,
+    pub score: usize,
+    pub distances: Vec<(Offset, usize)>,
+}
+
+impl<T: fmt::Display> fmt::Display for RankedTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let distances = self
+            .distances
+            .iter()
+            .map(|(offset, distance)| format!("{} {}", survey::offset_name(*offset), distance))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        write!(
+            f,
+            "{}. ({}, {}) height {}, score {}: {}",
+            self.rank, self.position.0, self.position.1, self.height, self.score, distances
+        )
+    }
+}
+
+/// The `k` trees with the highest scenic score, best first. Trees with the same score are
+/// ordered by position, top to bottom and then left to right.
+pub fn top_trees<T: Clone>(
+    trees: &Grid<T>,
+    survey: &Survey,
+    k: usize,
+    ties: Ties,
+) -> Vec<RankedTree<T>> {
+    let mut scores = trees
+        .positions()
+        .map(|position| (survey.score(position), position))
+        .collect::<Vec<(usize, Position)>>();
+    scores.sort_by_key(|&(score, (x, y))| (Reverse(score), y, x));
+
+    let cutoff = match (ties, k.checked_sub(1).and_then(|last| scores.get(last))) {
+        (Ties::Include, Some(&(last_score, _))) => scores
+            .iter()
+            .take_while(|(score, _)| *score >= last_score)
+            .count(),
+        _ => k.min(scores.len()),
+    };
+
+    let mut ranked: Vec<RankedTree<T>> = Vec::with_capacity(cutoff);
+    for (index, &(score, position)) in scores[..cutoff].iter().enumerate() {
+        let rank = match ranked.last() {
+            Some(previous) if previous.score == score => previous.rank,
+            _ => index + 1,
+        };
+
+        let distances = survey
+            .sightlines()
+            .iter()
+            .map(|lines| (lines.offset(), lines.distance(position)))
+            .collect();
+
+        ranked.push(RankedTree {
+            rank,
+            position,
+            height: trees[position].clone(),
+            score,
+            distances,
+        });
+    }
+
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::survey::{read_sample, CARDINAL};
+
+    fn get_top(k: usize, ties: Ties) -> Vec<(usize, Position, usize)> {
+        let trees = read_sample();
+        let survey = Survey::new(&trees, &CARDINAL);
+
+        top_trees(&trees, &survey, k, ties)
+            .into_iter()
+            .map(|tree| (tree.rank, tree.position, tree.score))
+            .collect()
+    }
+
+    #[test]
+    fn test_best_tree() {
+        let trees = read_sample();
+        let survey = Survey::new(&trees, &CARDINAL);
+        let best = top_trees(&trees, &survey, 1, Ties::Truncate);
+
+        assert_eq!(best.len(), 1);
+        assert_eq!(
+            best[0].to_string(),
+            "1. (2, 3) height 5, score 8: top 2, bottom 1, left 2, right 2"
+        );
+    }
+
+    #[test]
+    fn test_ties() {
+        let tied = [
+            (6, (1, 1), 1),
+            (6, (3, 1), 1),
+            (6, (2, 2), 1),
+            (6, (1, 3), 1),
+        ];
+
+        let truncated = get_top(7, Ties::Truncate);
+        assert_eq!(truncated.len(), 7);
+        assert_eq!(truncated[5..], tied[..2]);
+
+        let included = get_top(7, Ties::Include);
+        assert_eq!(included.len(), 9);
+        assert_eq!(included[5..], tied[..]);
+        assert_eq!(get_top(5, Ties::Include).len(), 5);
+    }
+
+    #[test]
+    fn test_limits() {
+        assert!(get_top(0, Ties::Include).is_empty());
+        assert_eq!(get_top(100, Ties::Truncate).len(), 25);
+        assert_eq!(get_top(100, Ties::Include).len(), 25);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::survey::{self, Survey};
+use grid::{Grid, Position};
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Pgm,
+    Ppm,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Option<ImageFormat> {
+        match path.extension()?.to_str()? {
+            "pgm" => Some(ImageFormat::Pgm),
+            "ppm" => Some(ImageFormat::Ppm),
+            _ => None,
+        }
+    }
+}
+
+/// Draws which trees are visible, first from any direction and then from each offset of the
+/// survey in turn, with `#` for a visible tree and `.` for a hidden one.
+pub fn render_visibility(survey: &Survey) -> String {
+    let draw = |is_visible: &dyn Fn(Position) -> bool| {
+        Grid::from_fn(
+            survey.width(),
+            survey.height(),
+            |position| match is_visible(position) {
+                true => '#',
+                false => '.',
+            },
+        )
+    };
+
+    let mut sections = vec![format!(
+        "Visible from any direction\n{}",
+        draw(&|position| survey.is_visible(position))
+    )];
+    for lines in survey.sightlines() {
+        sections.push(format!(
+            "Visible from {}\n{}",
+            survey::offset_name(lines.offset()),
+            draw(&|position| lines.is_visible(position))
+        ));
+    }
+
+    sections.join("\n")
+}
+
+/// Scales scores to `0..=255` logarithmically, since the best trees score orders of
+/// magnitude above the rest.
+pub fn intensities(scores: &Grid<usize>) -> Grid<u8> {
+    let max = scores.iter().map(|(_, score)| *score).max().unwrap_or(0);
+    let scale = (max as f64).ln_1p();
+
+    scores.map(|&score| match max {
+        0 => 0,
+        _ => ((score as f64).ln_1p() / scale * 255.0).round() as u8,
+    })
+}
+
+/// Writes every tree's height on a background going from black through red and yellow
+/// to white as its score grows.
+pub fn render_heatmap<T: fmt::Display>(trees: &Grid<T>, scores: &Grid<usize>) -> String {
+    let intensities = intensities(scores);
+    let mut output = String::new();
+
+    for (y, row) in trees.rows().enumerate() {
+        for (x, height) in row.iter().enumerate() {
+            let intensity = intensities[(x, y)];
+            let [r, g, b] = heat_colour(intensity);
+            let foreground = if intensity > 127 { 30 } else { 97 };
+            output.push_str(&format!(
+                "\x1b[{};48;2;{};{};{}m{}",
+                foreground, r, g, b, height
+            ));
+        }
+        output.push_str("\x1b[0m\n");
+    }
+
+    output
+}
+
+/// Writes the scores as a binary PGM in grey or a binary PPM in the heatmap colours,
+/// one pixel per tree.
+pub fn write_image(
+    scores: &Grid<usize>,
+    format: ImageFormat,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let intensities = intensities(scores);
+    let magic = match format {
+        ImageFormat::Pgm => "P5",
+        ImageFormat::Ppm => "P6",
+    };
+    write!(
+        writer,
+        "{}\n{} {}\n255\n",
+        magic,
+        intensities.width(),
+        intensities.height()
+    )?;
+
+    let pixels = intensities
+        .iter()
+        .flat_map(|(_, &intensity)| match format {
+            ImageFormat::Pgm => vec![intensity],
+            ImageFormat::Ppm => heat_colour(intensity).to_vec(),
+        })
+        .collect::<Vec<u8>>();
+
+    writer.write_all(&pixels)
+}
+
+fn heat_colour(intensity: u8) -> [u8; 3] {
+    let heat = intensity as usize * 3;
+    let channel = |offset: usize| heat.saturating_sub(offset).min(255) as u8;
+
+    [channel(0), channel(255), channel(510)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::survey::{read_sample, CARDINAL, DIAGONAL};
+
+    #[test]
+    fn test_visibility() {
+        let survey = Survey::new(&read_sample(), &CARDINAL);
+        let expected = "Visible from any direction
+#####
+###.#
+##.##
+#.#.#
+#####
+
+Visible from top
+#####
+.##..
+#....
+....#
+...#.
+
+Visible from bottom
+.....
+.....
+#....
+..#.#
+#####
+
+Visible from left
+#..#.
+##...
+#....
+#.#.#
+##.#.
+
+Visible from right
+...##
+..#.#
+##.##
+....#
+...##
+";
+        assert_eq!(render_visibility(&survey), expected);
+    }
+
+    #[test]
+    fn test_visibility_many_offsets() {
+        let offsets = [CARDINAL, DIAGONAL].concat();
+        let survey = Survey::new(&read_sample(), &offsets);
+        let rendered = render_visibility(&survey);
+
+        assert_eq!(rendered.matches("Visible from").count(), 9);
+        assert!(rendered.contains("Visible from (1, -1)\n#####\n##..#\n#..##\n..###\n....#\n"));
+    }
+
+    #[test]
+    fn test_intensities() {
+        let scores = Grid::from_rows(vec![vec![0, 1, 3, 15]]).unwrap();
+        let intensities = intensities(&scores);
+        assert_eq!(intensities.row(0), &[0, 64, 128, 255]);
+
+        let flat = Grid::filled(2, 2, 0);
+        assert_eq!(super::intensities(&flat), Grid::filled(2, 2, 0));
+    }
+
+    #[test]
+    fn test_heat_colour() {
+        assert_eq!(heat_colour(0), [0, 0, 0]);
+        assert_eq!(heat_colour(85), [255, 0, 0]);
+        assert_eq!(heat_colour(170), [255, 255, 0]);
+        assert_eq!(heat_colour(255), [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let trees = Grid::from_rows(vec![vec![1, 2]]).unwrap();
+        let scores = Grid::from_rows(vec![vec![0, 4]]).unwrap();
+        assert_eq!(
+            render_heatmap(&trees, &scores),
+            "\x1b[97;48;2;0;0;0m1\x1b[30;48;2;255;255;255m2\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_write_image() {
+        let scores = Grid::from_rows(vec![vec![0, 4]]).unwrap();
+
+        let mut pgm = vec![];
+        write_image(&scores, ImageFormat::Pgm, &mut pgm).unwrap();
+        assert_eq!(pgm, b"P5\n2 1\n255\n\x00\xff");
+
+        let mut ppm = vec![];
+        write_image(&scores, ImageFormat::Ppm, &mut ppm).unwrap();
+        assert_eq!(ppm, b"P6\n2 1\n255\n\x00\x00\x00\xff\xff\xff");
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ImageFormat::from_path(Path::new("heat.ppm")),
+            Some(ImageFormat::Ppm)
+        );
+        assert_eq!(
+            ImageFormat::from_path(Path::new("out/heat.pgm")),
+            Some(ImageFormat::Pgm)
+        );
+        assert_eq!(ImageFormat::from_path(Path::new("heat.png")), None);
+    }
+}
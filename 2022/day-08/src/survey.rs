@@ -0,0 +1,355 @@
+use grid::{Direction, Grid, Position};
+use std::fmt;
+
+/// A step vector to look along, as `(dx, dy)`.
+pub type Offset = (isize, isize);
+
+pub const CARDINAL: [Offset; 4] = [
+    Direction::Up.offset(),
+    Direction::Down.offset(),
+    Direction::Left.offset(),
+    Direction::Right.offset(),
+];
+
+pub const DIAGONAL: [Offset; 4] = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OffsetError {
+    Format(String),
+    Zero,
+    Empty,
+}
+
+impl fmt::Display for OffsetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OffsetError::Format(text) => {
+                write!(f, "Expected cardinal, diagonal or dx,dy, got '{}'", text)
+            }
+            OffsetError::Zero => write!(f, "Cannot look along 0,0"),
+            OffsetError::Empty => write!(f, "No directions given"),
+        }
+    }
+}
+
+/// Names the cardinal offsets the way the puzzle does; other offsets are written as vectors.
+pub fn offset_name(offset: Offset) -> String {
+    match offset {
+        (0, -1) => "top".to_string(),
+        (0, 1) => "bottom".to_string(),
+        (-1, 0) => "left".to_string(),
+        (1, 0) => "right".to_string(),
+        (dx, dy) => format!("({}, {})", dx, dy),
+    }
+}
+
+/// Parses a `+` separated set of directions, where each item is `cardinal`, `diagonal` or a
+/// step vector like `2,-1`. Repeated offsets are only kept once.
+pub fn parse_offsets(spec: &str) -> Result<Vec<Offset>, OffsetError> {
+    let mut offsets = vec![];
+    for item in spec
+        .split('+')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let parsed = match item {
+            "cardinal" => CARDINAL.to_vec(),
+            "diagonal" => DIAGONAL.to_vec(),
+            _ => vec![parse_offset(item)?],
+        };
+
+        for offset in parsed {
+            if !offsets.contains(&offset) {
+                offsets.push(offset);
+            }
+        }
+    }
+
+    match offsets.is_empty() {
+        true => Err(OffsetError::Empty),
+        false => Ok(offsets),
+    }
+}
+
+fn parse_offset(text: &str) -> Result<Offset, OffsetError> {
+    let format = || OffsetError::Format(text.to_string());
+    let (dx, dy) = text.split_once(',').ok_or_else(format)?;
+    let dx = dx.trim().parse().map_err(|_| format())?;
+    let dy = dy.trim().parse().map_err(|_| format())?;
+
+    match (dx, dy) {
+        (0, 0) => Err(OffsetError::Zero),
+        offset => Ok(offset),
+    }
+}
+
+/// Visibility and viewing distance of every tree when looking along one offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sightlines {
+    offset: Offset,
+    visible: Grid<bool>,
+    distances: Grid<usize>,
+}
+
+impl Sightlines {
+    /// Computes the sightlines with a monotonic stack per line of trees, in O(rows·cols).
+    ///
+    /// Each line is walked against the offset, starting from the tree at the edge the offset points
+    /// to, so the stack always holds the trees ahead of the current one that are not hidden
+    /// behind a taller or equal tree closer to it.
+    pub fn new<T: Ord>(trees: &Grid<T>, offset: Offset) -> Sightlines {
+        let mut visible = Grid::filled(trees.width(), trees.height(), false);
+        let mut distances = Grid::filled(trees.width(), trees.height(), 0);
+
+        let back = (-offset.0, -offset.1);
+        let mut stack: Vec<(usize, &T)> = vec![];
+
+        if offset != (0, 0) {
+            let starts = trees
+                .positions()
+                .filter(|&position| trees.step(position, offset).is_none());
+
+            for start in starts {
+                stack.clear();
+                let line =
+                    std::iter::once((start, &trees[start])).chain(trees.walk_by(start, back));
+
+                for (index, (position, height)) in line.enumerate() {
+                    while stack.last().is_some_and(|(_, other)| *other < height) {
+                        stack.pop();
+                    }
+
+                    match stack.last() {
+                        Some((blocker, _)) => distances[position] = index - blocker,
+                        None => {
+                            visible[position] = true;
+                            distances[position] = index;
+                        }
+                    }
+
+                    stack.push((index, height));
+                }
+            }
+        }
+
+        Sightlines {
+            offset,
+            visible,
+            distances,
+        }
+    }
+
+    pub fn offset(&self) -> Offset {
+        self.offset
+    }
+
+    /// Whether every tree ahead of `position` is shorter than it.
+    pub fn is_visible(&self, position: Position) -> bool {
+        self.visible[position]
+    }
+
+    /// The number of trees seen from `position`, including the tree that blocks the view.
+    pub fn distance(&self, position: Position) -> usize {
+        self.distances[position]
+    }
+}
+
+/// Sightlines of every tree along a set of offsets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Survey {
+    width: usize,
+    height: usize,
+    sightlines: Vec<Sightlines>,
+}
+
+impl Survey {
+    pub fn new<T: Ord>(trees: &Grid<T>, offsets: &[Offset]) -> Survey {
+        let sightlines = offsets
+            .iter()
+            .map(|&offset| Sightlines::new(trees, offset))
+            .collect();
+
+        Survey {
+            width: trees.width(),
+            height: trees.height(),
+            sightlines,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn sightlines(&self) -> &[Sightlines] {
+        &self.sightlines
+    }
+
+    pub fn is_visible(&self, position: Position) -> bool {
+        self.sightlines
+            .iter()
+            .any(|lines| lines.is_visible(position))
+    }
+
+    /// The offsets along which the tree at `position` can be seen from outside the grid.
+    pub fn visible_from(&self, position: Position) -> Vec<Offset> {
+        self.sightlines
+            .iter()
+            .filter(|lines| lines.is_visible(position))
+            .map(|lines| lines.offset())
+            .collect()
+    }
+
+    pub fn distances(&self, position: Position) -> Vec<usize> {
+        self.sightlines
+            .iter()
+            .map(|lines| lines.distance(position))
+            .collect()
+    }
+
+    /// The product of the viewing distances along every offset.
+    pub fn score(&self, position: Position) -> usize {
+        self.sightlines
+            .iter()
+            .map(|lines| lines.distance(position))
+            .product()
+    }
+
+    pub fn scores(&self) -> Grid<usize> {
+        Grid::from_fn(self.width, self.height, |position| self.score(position))
+    }
+}
+
+/// The example grid from the puzzle statement, for tests across the crate.
+#[cfg(test)]
+pub(crate) fn read_sample() -> Grid<u8> {
+    let content = std::fs::read_to_string("sample.txt").expect("File should exist");
+    content.parse().expect("valid trees")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_visible(trees: &Grid<u8>, position: Position, offsets: &[Offset]) -> bool {
+        let height = trees[position];
+
+        offsets.iter().any(|&offset| {
+            trees
+                .walk_by(position, offset)
+                .all(|(_, &other)| other < height)
+        })
+    }
+
+    fn calculate_score(trees: &Grid<u8>, position: Position, offsets: &[Offset]) -> usize {
+        let height = trees[position];
+
+        offsets
+            .iter()
+            .map(|&offset| {
+                let mut distance = 0;
+                for (_, &other) in trees.walk_by(position, offset) {
+                    distance += 1;
+                    if other >= height {
+                        break;
+                    }
+                }
+
+                distance
+            })
+            .product()
+    }
+
+    fn generate_grid(width: usize, height: usize, state: &mut u64) -> Grid<u8> {
+        let rows = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| {
+                        *state ^= *state << 13;
+                        *state ^= *state >> 7;
+                        *state ^= *state << 17;
+                        (*state % 10) as u8
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Grid::from_rows(rows).expect("a rectangular grid")
+    }
+
+    #[test]
+    fn test_sample() {
+        let trees = read_sample();
+        let survey = Survey::new(&trees, &CARDINAL);
+
+        assert_eq!(survey.distances((2, 1)), vec![1, 2, 1, 2]);
+        assert_eq!(survey.score((2, 3)), 8);
+        assert!(survey.is_visible((1, 1)));
+        assert!(!survey.is_visible((2, 2)));
+        assert_eq!(survey.visible_from((1, 1)), vec![(0, -1), (-1, 0)]);
+        assert_eq!(survey.scores().iter().map(|(_, s)| *s).max(), Some(8));
+    }
+
+    #[test]
+    fn test_parse_offsets() {
+        assert_eq!(parse_offsets("cardinal"), Ok(CARDINAL.to_vec()));
+        assert_eq!(parse_offsets("cardinal+diagonal").map(|o| o.len()), Ok(8));
+        assert_eq!(
+            parse_offsets("2,-1 + 0,1 + cardinal"),
+            Ok(vec![(2, -1), (0, 1), (0, -1), (-1, 0), (1, 0)])
+        );
+        assert_eq!(parse_offsets("0,0"), Err(OffsetError::Zero));
+        assert_eq!(parse_offsets("+"), Err(OffsetError::Empty));
+        assert_eq!(
+            parse_offsets("up"),
+            Err(OffsetError::Format("up".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_offset_name() {
+        assert_eq!(offset_name((0, -1)), "top");
+        assert_eq!(offset_name((1, -1)), "(1, -1)");
+    }
+
+    #[test]
+    fn test_diagonal() {
+        let trees = read_sample();
+        let survey = Survey::new(&trees, &DIAGONAL);
+
+        assert_eq!(survey.distances((2, 2)), vec![1, 2, 1, 1]);
+        assert!(survey.is_visible((3, 3)));
+        assert!(!survey.is_visible((2, 2)));
+    }
+
+    #[test]
+    fn test_random_grids() {
+        let mut state: u64 = 0x2545F4914F6CDD1D;
+        let offsets = [(0, -1), (0, 1), (-1, 0), (1, 0), (1, 1), (-2, 1)];
+
+        for (width, height) in [(1, 1), (1, 7), (6, 1), (5, 5), (13, 8), (30, 30)] {
+            let trees = generate_grid(width, height, &mut state);
+            let survey = Survey::new(&trees, &offsets);
+
+            for position in trees.positions() {
+                assert_eq!(
+                    survey.is_visible(position),
+                    is_visible(&trees, position, &offsets),
+                    "visibility at {:?} in\n{}",
+                    position,
+                    trees
+                );
+                assert_eq!(
+                    survey.score(position),
+                    calculate_score(&trees, position, &offsets),
+                    "score at {:?} in\n{}",
+                    position,
+                    trees
+                );
+            }
+        }
+    }
+}
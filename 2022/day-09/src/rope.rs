@@ -0,0 +1,176 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A knot position, with `x` growing to the right and `y` growing up.
+pub type Knot = (i32, i32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "L" => Ok(Direction::Left),
+            "R" => Ok(Direction::Right),
+            "U" => Ok(Direction::Up),
+            "D" => Ok(Direction::Down),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A rope of knots starting at the origin, pulled around by its head.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rope {
+    knots: Vec<Knot>,
+}
+
+impl Rope {
+    pub fn new(length: usize) -> Rope {
+        assert!(length > 0, "a rope needs at least one knot");
+
+        Rope {
+            knots: vec![(0, 0); length],
+        }
+    }
+
+    /// The knots from the head to the tail.
+    pub fn knots(&self) -> &[Knot] {
+        &self.knots
+    }
+
+    pub fn head(&self) -> Knot {
+        self.knots[0]
+    }
+
+    pub fn tail(&self) -> Knot {
+        self.knots[self.knots.len() - 1]
+    }
+
+    /// Moves the head one step and lets every other knot follow the one before it.
+    pub fn step(&mut self, direction: Direction) {
+        let (dx, dy) = direction.offset();
+        self.knots[0].0 += dx;
+        self.knots[0].1 += dy;
+
+        for index in 1..self.knots.len() {
+            self.knots[index] = follow(self.knots[index - 1], self.knots[index]);
+        }
+    }
+}
+
+/// A knot stays put while it touches the knot ahead of it, including diagonally.
+/// Otherwise it moves one step towards it along each axis where they differ.
+fn follow(ahead: Knot, knot: Knot) -> Knot {
+    let x_diff = ahead.0 - knot.0;
+    let y_diff = ahead.1 - knot.1;
+
+    if x_diff.abs() <= 1 && y_diff.abs() <= 1 {
+        return knot;
+    }
+
+    (knot.0 + x_diff.signum(), knot.1 + y_diff.signum())
+}
+
+/// Draws the knots like the puzzle statement: `H` for the head, the index for the other
+/// knots and `s` for the start, with the earlier knot shown where several overlap.
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xs = self.knots.iter().map(|knot| knot.0).chain([0]);
+        let ys = self.knots.iter().map(|knot| knot.1).chain([0]);
+        let (x_min, x_max) = (xs.clone().min().unwrap(), xs.max().unwrap());
+        let (y_min, y_max) = (ys.clone().min().unwrap(), ys.max().unwrap());
+
+        for y in (y_min..=y_max).rev() {
+            for x in x_min..=x_max {
+                let glyph = match self.knots.iter().position(|&knot| knot == (x, y)) {
+                    Some(0) => 'H',
+                    Some(index) => char::from_digit(index as u32, 36).unwrap_or('#'),
+                    None if (x, y) == (0, 0) => 's',
+                    None => '.',
+                };
+                write!(f, "{}", glyph)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follow() {
+        for x in -1..=1 {
+            for y in -1..=1 {
+                assert_eq!(follow((x, y), (0, 0)), (0, 0));
+            }
+        }
+
+        assert_eq!(follow((2, 0), (0, 0)), (1, 0));
+        assert_eq!(follow((0, -2), (0, 0)), (0, -1));
+        assert_eq!(follow((2, 1), (0, 0)), (1, 1));
+        assert_eq!(follow((-1, 2), (0, 0)), (-1, 1));
+        assert_eq!(follow((-2, -2), (0, 0)), (-1, -1));
+    }
+
+    #[test]
+    fn test_step() {
+        let mut rope = Rope::new(2);
+        for direction in [Direction::Right, Direction::Right, Direction::Up] {
+            rope.step(direction);
+        }
+        assert_eq!(rope.knots(), &[(2, 1), (1, 0)]);
+
+        rope.step(Direction::Up);
+        assert_eq!(rope.head(), (2, 2));
+        assert_eq!(rope.tail(), (2, 1));
+    }
+
+    #[test]
+    fn test_long_rope() {
+        let mut rope = Rope::new(10);
+        for _ in 0..5 {
+            rope.step(Direction::Right);
+        }
+        for _ in 0..8 {
+            rope.step(Direction::Up);
+        }
+
+        assert_eq!(rope.head(), (5, 8));
+        assert_eq!(rope.tail(), (0, 0));
+        assert_eq!(rope.knots()[4], (5, 4));
+        assert_eq!(rope.knots()[5], (4, 4));
+    }
+
+    #[test]
+    fn test_display() {
+        let mut rope = Rope::new(3);
+        rope.step(Direction::Right);
+        rope.step(Direction::Right);
+        rope.step(Direction::Up);
+
+        assert_eq!(rope.to_string(), "..H\n21.\n");
+    }
+}
@@ -0,0 +1,482 @@
+use std::fmt;
+use std::iter;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+/// A position in a grid, as `(x, y)` with the origin in the top left corner.
+pub type Position = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// The `(dx, dy)` step of the direction, where up decreases `y`.
+    pub const fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridError {
+    Empty,
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidCell {
+        x: usize,
+        y: usize,
+        text: String,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "Grid has no cells"),
+            GridError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row {} has {} cells, expected {}",
+                row + 1,
+                found,
+                expected
+            ),
+            GridError::InvalidCell { x, y, text } => {
+                write!(f, "Invalid cell '{}' at {},{}", text, x, y)
+            }
+        }
+    }
+}
+
+/// A rectangular grid stored in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, GridError> {
+        let width = rows.first().map(|row| row.len()).unwrap_or(0);
+        if width == 0 {
+            return Err(GridError::Empty);
+        }
+
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(GridError::RaggedRow {
+                    row: y,
+                    expected: width,
+                    found: row.len(),
+                });
+            }
+
+            cells.extend(row);
+        }
+
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Panics if `width` or `height` is zero, since [`Grid::from_rows`] rejects empty grids too.
+    pub fn filled(width: usize, height: usize, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        assert_not_empty(width, height);
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Panics if `width` or `height` is zero, since [`Grid::from_rows`] rejects empty grids too.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Position) -> T) -> Grid<T> {
+        assert_not_empty(width, height);
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut f)
+            .collect();
+
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses one row per line, with the cells separated by commas, whitespace or both.
+    pub fn parse_separated(input: &str) -> Result<Grid<T>, GridError>
+    where
+        T: FromStr,
+    {
+        let rows = input
+            .lines()
+            .enumerate()
+            .map(|(y, line)| {
+                line.split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|text| !text.is_empty())
+                    .enumerate()
+                    .map(|(x, text)| {
+                        text.parse().map_err(|_| GridError::InvalidCell {
+                            x,
+                            y,
+                            text: text.to_string(),
+                        })
+                    })
+                    .collect::<Result<Vec<T>, GridError>>()
+            })
+            .collect::<Result<Vec<Vec<T>>, GridError>>()?;
+
+        Grid::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Position) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn is_edge(&self, (x, y): Position) -> bool {
+        self.contains((x, y)) && (x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1)
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.index_of(position).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.index_of(position).map(|index| &mut self.cells[index])
+    }
+
+    /// Panics if `y` is outside the grid.
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "row {} should be inside the grid", y);
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    /// Panics if `x` is outside the grid.
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        assert!(x < self.width, "column {} should be inside the grid", x);
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// All positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The position one step from `position` in `direction`, if it is inside the grid.
+    pub fn neighbour(&self, position: Position, direction: Direction) -> Option<Position> {
+        self.step(position, direction.offset())
+    }
+
+    pub fn neighbours(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.neighbour(position, direction))
+    }
+
+    /// The position `(dx, dy)` away from `position`, if it is inside the grid.
+    pub fn step(&self, (x, y): Position, (dx, dy): (isize, isize)) -> Option<Position> {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+        self.contains((x, y)).then_some((x, y))
+    }
+
+    /// Cells from `position` (exclusive) towards the edge of the grid, nearest first.
+    pub fn walk(
+        &self,
+        position: Position,
+        direction: Direction,
+    ) -> impl Iterator<Item = (Position, &T)> + '_ {
+        self.walk_by(position, direction.offset())
+    }
+
+    /// Like [`Grid::walk`], but with an arbitrary step.
+    pub fn walk_by(
+        &self,
+        position: Position,
+        offset: (isize, isize),
+    ) -> impl Iterator<Item = (Position, &T)> + '_ {
+        let first = match offset {
+            (0, 0) => None,
+            _ => self.step(position, offset),
+        };
+
+        iter::successors(first, move |&position| self.step(position, offset))
+            .map(|position| (position, &self[position]))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    fn index_of(&self, (x, y): Position) -> Option<usize> {
+        self.contains((x, y)).then_some(y * self.width + x)
+    }
+}
+
+fn assert_not_empty(width: usize, height: usize) {
+    assert!(
+        width > 0 && height > 0,
+        "a grid needs at least one cell, got {}x{}",
+        width,
+        height
+    );
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position)
+            .expect("position should be inside the grid")
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        self.get_mut(position)
+            .expect("position should be inside the grid")
+    }
+}
+
+/// Parses one digit per cell, one row per line.
+impl FromStr for Grid<u8> {
+    type Err = GridError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let rows = input
+            .lines()
+            .enumerate()
+            .map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(x, char)| match char.to_digit(10) {
+                        Some(digit) => Ok(digit as u8),
+                        None => Err(GridError::InvalidCell {
+                            x,
+                            y,
+                            text: char.to_string(),
+                        }),
+                    })
+                    .collect::<Result<Vec<u8>, GridError>>()
+            })
+            .collect::<Result<Vec<Vec<u8>>, GridError>>()?;
+
+        Grid::from_rows(rows)
+    }
+}
+
+/// Writes the cells of each row without separators, one row per line.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "30373\n25512\n65332\n33549\n35390\n";
+
+    #[test]
+    fn test_parse() {
+        let grid = SAMPLE.parse::<Grid<u8>>().unwrap();
+        assert_eq!(grid.width(), 5);
+        assert_eq!(grid.height(), 5);
+        assert_eq!(grid.get((3, 0)), Some(&7));
+        assert_eq!(grid.get((5, 0)), None);
+        assert_eq!(grid.to_string(), SAMPLE);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<Grid<u8>>(), Err(GridError::Empty));
+        assert_eq!(
+            "123\n45\n".parse::<Grid<u8>>(),
+            Err(GridError::RaggedRow {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            "12\n4x\n".parse::<Grid<u8>>(),
+            Err(GridError::InvalidCell {
+                x: 1,
+                y: 1,
+                text: "x".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_separated() {
+        let grid = Grid::<u32>::parse_separated("10, 200,3\n4 5\t60\n").unwrap();
+        assert_eq!(grid.row(0), &[10, 200, 3]);
+        assert_eq!(grid.row(1), &[4, 5, 60]);
+
+        assert_eq!(
+            Grid::<u32>::parse_separated("1,2\n3,-4\n"),
+            Err(GridError::InvalidCell {
+                x: 1,
+                y: 1,
+                text: "-4".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = SAMPLE.parse::<Grid<u8>>().unwrap();
+        assert_eq!(grid.row(1), &[2, 5, 5, 1, 2]);
+        assert_eq!(
+            grid.column(2).copied().collect::<Vec<u8>>(),
+            vec![3, 5, 3, 5, 3]
+        );
+        assert_eq!(
+            grid.column(4).rev().copied().collect::<Vec<u8>>(),
+            vec![0, 9, 2, 2, 3]
+        );
+        assert_eq!(grid.rows().count(), 5);
+        assert_eq!(grid.columns().count(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "column 5 should be inside the grid")]
+    fn test_column_out_of_bounds() {
+        let grid = SAMPLE.parse::<Grid<u8>>().unwrap();
+        let _ = grid.column(5);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 5 should be inside the grid")]
+    fn test_row_out_of_bounds() {
+        let grid = SAMPLE.parse::<Grid<u8>>().unwrap();
+        grid.row(5);
+    }
+
+    #[test]
+    #[should_panic(expected = "a grid needs at least one cell, got 0x2")]
+    fn test_filled_empty() {
+        Grid::filled(0, 2, false);
+    }
+
+    #[test]
+    fn test_walk() {
+        let grid = SAMPLE.parse::<Grid<u8>>().unwrap();
+        let heights = |direction| {
+            grid.walk((2, 1), direction)
+                .map(|(_, height)| *height)
+                .collect::<Vec<u8>>()
+        };
+
+        assert_eq!(heights(Direction::Up), vec![3]);
+        assert_eq!(heights(Direction::Down), vec![3, 5, 3]);
+        assert_eq!(heights(Direction::Left), vec![5, 2]);
+        assert_eq!(heights(Direction::Right), vec![1, 2]);
+        assert_eq!(grid.walk((0, 0), Direction::Left).count(), 0);
+        assert_eq!(grid.walk_by((0, 0), (1, 1)).count(), 4);
+        assert_eq!(grid.walk_by((0, 0), (0, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::filled(3, 2, false);
+        assert_eq!(
+            grid.neighbours((0, 0)).collect::<Vec<Position>>(),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(grid.neighbours((1, 1)).count(), 3);
+        assert!(grid.is_edge((1, 1)));
+        assert!(!grid.is_edge((3, 1)));
+    }
+
+    #[test]
+    fn test_from_fn() {
+        let grid = Grid::from_fn(3, 2, |(x, y)| x + 10 * y);
+        assert_eq!(grid.row(1), &[10, 11, 12]);
+        assert_eq!(grid.to_string(), "012\n101112\n");
+    }
+
+    #[test]
+    fn test_map_and_display() {
+        let mut grid = Grid::filled(3, 2, false);
+        grid[(1, 0)] = true;
+        grid[(2, 1)] = true;
+
+        let pixels = grid.map(|&lit| if lit { '#' } else { '.' });
+        assert_eq!(pixels.to_string(), ".#.\n..#\n");
+    }
+}